@@ -1,24 +1,203 @@
 // src/cli/mod.rs
 
-use clap::{Parser, Subcommand};
-// Import the SQLInjectionScanner type from its module
-use crate::modules::auxiliary::sql_injection::SQLInjectionScanner;
+use clap::{Parser, Subcommand, ValueEnum};
+use crate::core::{
+    AuditLog, Database, EncodingPipeline, HttpRequest, LogConfig, LogDestination, LogFormat, Logger, ModuleHandler, ScanRecord,
+    RiskLevel, ScanResult, Safety, Scope, Session, SeverityLevel,
+};
+use crate::import::{HarArchive, OpenApiSpec, PostmanCollection};
+use crate::modules::auxiliary::crawler::{Crawler, CrawlerConfig};
+use crate::modules::auxiliary::graphql::GraphQlScanner;
+use crate::report::{render_junit, ReportFormat, ReportGenerator};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Exit code when the command succeeded and no findings reached the `--fail-on` threshold
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit code when findings at or above the `--fail-on` threshold were reported
+pub const EXIT_FINDINGS: u8 = 1;
+/// Exit code when the scanner itself failed (bad arguments, network or database errors)
+pub const EXIT_ERROR: u8 = 2;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  Success; no findings at or above the --fail-on threshold
+  1  Findings at or above the --fail-on threshold were reported
+  2  Scanner error (invalid arguments, network or database failure)";
 
 
 #[derive(Parser)]
 #[command(name = "redlines-x")]
 #[command(about = "Advanced Security Scanner CLI", long_about = None)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Log level (off, error, warn, info, debug, trace); defaults to RUST_LOG, then info
+    #[arg(long, global = true, value_name = "LEVEL")]
+    pub log_level: Option<log::LevelFilter>,
+    /// Log record format
+    #[arg(long, global = true, value_enum, default_value_t = LogFormatArg::Text)]
+    pub log_format: LogFormatArg,
+    /// Where to write logs
+    #[arg(long, global = true, value_enum, default_value_t = LogDestinationArg::File)]
+    pub log_dest: LogDestinationArg,
+    /// Log file used when logging to a file
+    #[arg(long, global = true, value_name = "PATH", default_value = "redlines.log")]
+    pub log_file: PathBuf,
+    /// Also write each scan's logs to `<DIR>/<scan_id>.log`
+    #[arg(long, global = true, value_name = "DIR")]
+    pub scan_log_dir: Option<PathBuf>,
+    /// JSON scope definition (hosts, cidrs, path_prefixes, excluded_paths, case_insensitive_exclusions); defaults to the target's host
+    #[arg(long, global = true, value_name = "PATH")]
+    pub scope: Option<PathBuf>,
+    /// Turn off safe mode, allowing state-changing payloads when a scan opts into them
+    #[arg(long, global = true)]
+    pub disable_safe_mode: bool,
+    /// Replay POST, PUT, PATCH, DELETE and other requests that may change data
+    /// by themselves; safe mode still blocks data-modifying SQL
+    #[arg(long, global = true)]
+    pub allow_unsafe_methods: bool,
+    /// Append-only audit trail of every request sent
+    #[arg(long, global = true, value_name = "PATH", default_value = "redlines-audit.jsonl")]
+    pub audit_log: PathBuf,
+    /// Directory of extra YAML/JSON payload libraries, merged with the built-in ones (repeatable)
+    #[arg(long = "payload-dir", global = true, value_name = "DIR")]
+    pub payload_dirs: Vec<PathBuf>,
+    /// Extra parameter names (one per line) tried when mining for hidden parameters
+    #[arg(long, global = true, value_name = "PATH")]
+    pub param_wordlist: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Builds the logging configuration from the command line flags
+    pub fn log_config(&self) -> LogConfig {
+        LogConfig {
+            level: self.log_level,
+            format: match self.log_format {
+                LogFormatArg::Text => LogFormat::Text,
+                LogFormatArg::Json => LogFormat::Json,
+            },
+            destination: match self.log_dest {
+                LogDestinationArg::Stderr => LogDestination::Stderr,
+                LogDestinationArg::File => LogDestination::File,
+                LogDestinationArg::Both => LogDestination::Both,
+            },
+            file: self.log_file.clone(),
+            scan_log_dir: self.scan_log_dir.clone(),
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Run SQL Injection scan
     SqlInjection {
+        #[arg(short, long, required_unless_present = "site_map")]
+        url: Option<String>,
+        /// Test every endpoint of a stored site map (see `crawl`) instead of a single request
+        #[arg(long, value_name = "ID")]
+        site_map: Option<String>,
+        /// Exit with code 1 if any finding is at or above this severity (low, medium, high, critical)
+        #[arg(long, value_name = "SEVERITY")]
+        fail_on: Option<SeverityLevel>,
+        /// Write the scan result as JUnit XML to this file
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
+        /// Highest payload risk to send (read-only, heavy, state-changing)
+        #[arg(long, value_name = "LEVEL", default_value_t = RiskLevel::ReadOnly)]
+        risk: RiskLevel,
+        /// HTTP method of the request to test
+        #[arg(short = 'X', long, default_value = "GET")]
+        method: String,
+        /// Request body (form, JSON or XML); its fields are tested as well
+        #[arg(short, long)]
+        data: Option<String>,
+        /// Extra request header as "Name: value" (repeatable)
+        #[arg(short = 'H', long = "header", value_name = "HEADER", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Cookies to send, e.g. "session=abc; lang=en"
+        #[arg(long)]
+        cookie: Option<String>,
+        /// Encoders applied to every payload, in order, before the encoding its
+        /// location needs (url, double-url, html, unicode, base64, json or none),
+        /// e.g. "base64"
+        #[arg(long, value_name = "CHAIN")]
+        encode: Option<EncodingPipeline>,
+    },
+    /// Crawl a target and store its site map for later scans
+    Crawl {
+        #[arg(short, long)]
+        url: String,
+        /// Links followed from the start URL
+        #[arg(long, default_value_t = CrawlerConfig::default().max_depth)]
+        max_depth: usize,
+        /// Pages requested at most
+        #[arg(long, default_value_t = CrawlerConfig::default().max_pages)]
+        max_pages: usize,
+        /// Extra request header as "Name: value" (repeatable)
+        #[arg(short = 'H', long = "header", value_name = "HEADER", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Cookies to send, e.g. "session=abc; lang=en"
+        #[arg(long)]
+        cookie: Option<String>,
+    },
+    /// Find a GraphQL endpoint, report enabled introspection and store a site
+    /// map of queries generated from its schema
+    Graphql {
+        /// The GraphQL endpoint, or any URL of the site to look for one
         #[arg(short, long)]
         url: String,
+        /// Also generate mutations; scanning them changes data on the server
+        #[arg(long)]
+        include_mutations: bool,
+        /// Extra request header as "Name: value" (repeatable)
+        #[arg(short = 'H', long = "header", value_name = "HEADER", value_parser = parse_header)]
+        headers: Vec<(String, String)>,
+        /// Cookies to send, e.g. "session=abc; lang=en"
+        #[arg(long)]
+        cookie: Option<String>,
+    },
+    /// Import endpoints from an API description or recorded traffic into a stored site map
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// List site maps stored in the database
+    SiteMaps,
+    /// List scans stored in the database
+    Scans,
+    /// Generate a report for a stored scan
+    Report {
+        /// ID of the scan to report on
+        scan_id: String,
+        /// Report format to generate
+        #[arg(short, long, value_enum, default_value_t = ReportFormatArg::All)]
+        format: ReportFormatArg,
+        /// Directory to write the report files to
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        /// Directory containing custom `report.html` / `report.md` templates
+        #[arg(long)]
+        template_dir: Option<PathBuf>,
+    },
+    /// Export the request audit trail for a time window
+    Audit {
+        /// Only include requests sent at or after this time (RFC 3339 or YYYY-MM-DD)
+        #[arg(long, value_parser = parse_time)]
+        since: Option<DateTime<Utc>>,
+        /// Only include requests sent before this time (RFC 3339 or YYYY-MM-DD)
+        #[arg(long, value_parser = parse_time)]
+        until: Option<DateTime<Utc>>,
+        /// Only include requests belonging to this scan
+        #[arg(long)]
+        scan_id: Option<String>,
+        /// Export format
+        #[arg(short, long, value_enum, default_value_t = AuditFormatArg::Jsonl)]
+        format: AuditFormatArg,
+        /// File to write the export to; prints to stdout when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
     /// List available modules
     List,
@@ -26,21 +205,479 @@ pub enum Commands {
     Version,
 }
 
-pub async fn run_cli() {
-    let cli = Cli::parse();
+/// Formats `import` understands
+#[derive(Subcommand)]
+pub enum ImportSource {
+    /// Expand every operation of an OpenAPI 3 document (JSON or YAML) into an endpoint
+    Openapi {
+        /// Path of the OpenAPI document
+        file: PathBuf,
+        /// API base URL; required when the document's server URL is relative or missing
+        #[arg(long)]
+        base_url: Option<String>,
+        #[command(flatten)]
+        auth: SessionArgs,
+    },
+    /// Import the requests of a browser recording; its cookies and credential
+    /// headers are saved as a session
+    Har {
+        /// Path of the HAR file
+        file: PathBuf,
+        /// Origin whose requests are imported; defaults to the first page recorded
+        #[arg(long)]
+        origin: Option<String>,
+        #[command(flatten)]
+        auth: SessionArgs,
+    },
+    /// Import every request of a Postman collection (v2.0 or v2.1); its auth
+    /// is saved as a session
+    Postman {
+        /// Path of the exported collection
+        file: PathBuf,
+        #[command(flatten)]
+        auth: SessionArgs,
+    },
+}
+
+/// Credentials used for requests to an imported API
+#[derive(clap::Args)]
+pub struct SessionArgs {
+    /// Stored session whose headers, cookies and variables authenticate requests
+    #[arg(long, value_name = "ID")]
+    pub session: Option<String>,
+    /// Extra request header as "Name: value" (repeatable)
+    #[arg(short = 'H', long = "header", value_name = "HEADER", value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Cookies to send, e.g. "session=abc; lang=en"
+    #[arg(long)]
+    pub cookie: Option<String>,
+    /// Session variable as NAME=VALUE, e.g. `token=...` for bearer auth (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+}
+
+impl SessionArgs {
+    /// The stored session, if any, with the command line credentials added
+    fn session(&self, db: &Database, target: &str) -> Result<Session, Box<dyn std::error::Error>> {
+        let mut session = match &self.session {
+            Some(id) => db.get_session(id)?.ok_or_else(|| format!("Session '{}' not found", id))?,
+            None => Session::new(target),
+        };
+        for (name, value) in &self.headers {
+            session.headers.retain(|key, _| !key.eq_ignore_ascii_case(name));
+            session.headers.insert(name.clone(), value.clone());
+        }
+        let mut cookies = HttpRequest::get(target);
+        if let Some(cookie) = &self.cookie {
+            cookies.set_header("Cookie", cookie);
+        }
+        for (name, value) in cookies.cookies() {
+            session.add_cookie(&name, &value);
+        }
+        for (name, value) in &self.variables {
+            session.variables.insert(name.clone(), value.clone());
+        }
+        Ok(session)
+    }
+}
+
+/// Log formats selectable from the command line
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LogFormatArg {
+    Text,
+    Json,
+}
+
+/// Log destinations selectable from the command line
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LogDestinationArg {
+    Stderr,
+    File,
+    Both,
+}
+
+/// Audit trail export formats
+#[derive(Clone, Copy, ValueEnum)]
+pub enum AuditFormatArg {
+    Jsonl,
+    Csv,
+}
+
+/// Parses an RFC 3339 timestamp or a `YYYY-MM-DD` date (midnight UTC)
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| format!("invalid time '{}' (expected RFC 3339 or YYYY-MM-DD)", value))
+}
+
+/// Loads the `--scope` file, or scopes to the target's host, checks the
+/// target against it and enforces it for all requests
+fn activate_scope(path: Option<&PathBuf>, url: &str) -> Result<Scope, Box<dyn std::error::Error>> {
+    let scope = match path {
+        Some(path) => Scope::load(path).map_err(|e| format!("Failed to load scope {}: {}", path.display(), e))?,
+        None => Scope::for_target(url)?,
+    };
+    scope.check(url)?;
+    Scope::activate(scope.clone());
+    Ok(scope)
+}
+
+/// Adds the `-H` headers and `--cookie` value to a request
+fn apply_headers(request: &mut HttpRequest, headers: &[(String, String)], cookie: Option<&str>) {
+    for (name, value) in headers {
+        request.set_header(name, value);
+    }
+    if let Some(cookie) = cookie {
+        request.set_header("Cookie", cookie);
+    }
+}
 
+/// Prints the findings of a finished scan, most severe first, and its warnings
+fn print_summary(result: &ScanResult) {
+    let counts: Vec<String> = result
+        .severity_counts()
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(severity, count)| format!("{} {}", count, severity))
+        .collect();
+    let total = result.vulnerabilities.len();
+    if counts.is_empty() {
+        println!("Scan finished in {:.2?}: no findings", result.duration);
+    } else {
+        let plural = if total == 1 { "" } else { "s" };
+        println!("Scan finished in {:.2?}: {} finding{} ({})", result.duration, total, plural, counts.join(", "));
+    }
+
+    let mut findings: Vec<_> = result.vulnerabilities.iter().collect();
+    findings.sort_by_key(|v| std::cmp::Reverse(v.severity));
+    for vuln in findings {
+        println!("  [{}] {}", vuln.severity, vuln.title);
+    }
+    for warning in &result.warnings {
+        println!("  Note: {}", warning);
+    }
+}
+
+/// Parses a `NAME=VALUE` session variable
+fn parse_variable(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+        _ => Err(format!("invalid variable '{}' (expected NAME=VALUE)", value)),
+    }
+}
+
+/// Parses a `Name: value` header
+fn parse_header(value: &str) -> Result<(String, String), String> {
+    match value.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("invalid header '{}' (expected \"Name: value\")", value)),
+    }
+}
+
+/// Report formats selectable from the command line
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ReportFormatArg {
+    Html,
+    Markdown,
+    All,
+}
+
+impl ReportFormatArg {
+    fn formats(&self) -> Vec<ReportFormat> {
+        match self {
+            ReportFormatArg::Html => vec![ReportFormat::Html],
+            ReportFormatArg::Markdown => vec![ReportFormat::Markdown],
+            ReportFormatArg::All => vec![ReportFormat::Html, ReportFormat::Markdown],
+        }
+    }
+}
+
+pub async fn run_cli(
+    cli: Cli,
+    module_handler: ModuleHandler,
+    db: Database,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
     match cli.command {
-        Commands::SqlInjection { url } => {
+        Commands::SqlInjection { url, site_map, fail_on, junit, risk, method, data, headers, cookie, encode } => {
+            let site_map = match &site_map {
+                Some(id) => Some(db.get_site_map(id)?.ok_or_else(|| format!("Site map '{}' not found", id))?),
+                None => None,
+            };
+            let url = url.or_else(|| site_map.as_ref().map(|m| m.target.clone())).unwrap_or_default();
             println!("Running SQL Injection scan on: {}", url);
-            let scanner = SQLInjectionScanner::new();
-            let result = scanner.run_scan(&url).await;
-            println!("{:?}", result);
+            let scope = activate_scope(cli.scope.as_ref(), &url)?;
+
+            Safety::set_safe_mode(!cli.disable_safe_mode);
+            Safety::set_max_risk(risk);
+            Safety::set_unsafe_methods(cli.allow_unsafe_methods);
+            if Safety::max_risk() < risk {
+                println!("Safe mode is on: {} payloads stay blocked (use --disable-safe-mode)", risk);
+            }
+            if let Some(encoding) = &encode {
+                println!("Encoding payloads with: {}", encoding);
+            }
+            EncodingPipeline::select(encode);
+
+            let mut request = HttpRequest::get(&url);
+            request.method = method.to_uppercase();
+            request.body = data;
+            apply_headers(&mut request, &headers, cookie.as_deref());
+            if site_map.is_none() && !Safety::allows_request(request.risk()) {
+                return Err(format!(
+                    "Not scanning: a {} request may change data each time it is sent; \
+                     pass --allow-unsafe-methods to test it",
+                    request.method
+                )
+                .into());
+            }
+
+            let scan_id = uuid::Uuid::new_v4().to_string();
+            let started_at = chrono::Utc::now();
+            Logger::scan_start(&scan_id, "sql_injection", &url);
+
+            let result = match &site_map {
+                Some(site_map) => {
+                    let session = match &site_map.session {
+                        Some(id) => Some(db.get_session(id)?.ok_or_else(|| format!("Session '{}' not found", id))?),
+                        None => None,
+                    };
+                    let mut endpoints = site_map.endpoints.clone();
+                    for endpoint in &mut endpoints {
+                        if let Some(session) = &session {
+                            session.apply(&mut endpoint.request);
+                        }
+                        apply_headers(&mut endpoint.request, &headers, cookie.as_deref());
+                    }
+                    println!("Testing {} endpoints of site map {}", endpoints.len(), site_map.id);
+                    let blocked = endpoints.iter().filter(|e| !Safety::allows_request(e.request.risk())).count();
+                    if blocked > 0 {
+                        println!(
+                            "Skipping {} endpoints whose method may change data (use --allow-unsafe-methods)",
+                            blocked
+                        );
+                    }
+                    module_handler.run_module_endpoints("sql_injection", &endpoints).await
+                }
+                None => module_handler.run_module_request("sql_injection", &request).await,
+            };
+            let result = match result {
+                Ok(result) => {
+                    Logger::scan_complete(&url, result.vulnerabilities.len(), result.duration);
+                    result
+                }
+                Err(e) => {
+                    Logger::scan_failed(&url, &e.to_string(), (chrono::Utc::now() - started_at).to_std().unwrap_or_default());
+                    return Err(e.to_string().into());
+                }
+            };
+            print_summary(&result);
+
+            let record = ScanRecord {
+                id: scan_id,
+                target: url,
+                module: "sql_injection".to_string(),
+                started_at,
+                scope: Some(scope),
+                result,
+            };
+            db.save_scan(&record)?;
+            println!("Scan saved with ID: {}", record.id);
+
+            if let Some(path) = junit {
+                std::fs::write(&path, render_junit(std::slice::from_ref(&record), fail_on))?;
+                println!("JUnit report written to {}", path.display());
+            }
+
+            if let Some(threshold) = fail_on
+                && record.result.has_findings_at_or_above(threshold)
+            {
+                println!("Findings at or above {} severity were reported", threshold);
+                return Ok(ExitCode::from(EXIT_FINDINGS));
+            }
+        }
+        Commands::Crawl { url, max_depth, max_pages, headers, cookie } => {
+            println!("Crawling: {}", url);
+            activate_scope(cli.scope.as_ref(), &url)?;
+
+            let crawl_id = uuid::Uuid::new_v4().to_string();
+            let started_at = chrono::Utc::now();
+            Logger::scan_start(&crawl_id, "crawler", &url);
+            let mut start = HttpRequest::get(&url);
+            apply_headers(&mut start, &headers, cookie.as_deref());
+
+            let crawler = Crawler::new(CrawlerConfig { max_depth, max_pages });
+            let result = crawler.crawl(&start).await;
+            let elapsed = (chrono::Utc::now() - started_at).to_std().unwrap_or_default();
+            let mut site_map = match result {
+                Ok(site_map) => {
+                    Logger::scan_complete(&url, 0, elapsed);
+                    site_map
+                }
+                Err(e) => {
+                    Logger::scan_failed(&url, &e.to_string(), elapsed);
+                    return Err(e.to_string().into());
+                }
+            };
+            site_map.id = crawl_id;
+
+            for endpoint in &site_map.endpoints {
+                println!(
+                    "{:>4}  {:<6} {} [{}]",
+                    endpoint.status.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string()),
+                    endpoint.request.method,
+                    endpoint.request.url,
+                    endpoint.parameters.join(", ")
+                );
+            }
+            db.save_site_map(&site_map)?;
+            println!("Site map with {} endpoints saved with ID: {}", site_map.endpoints.len(), site_map.id);
+        }
+        Commands::Graphql { url, include_mutations, headers, cookie } => {
+            println!("Looking for a GraphQL endpoint at: {}", url);
+            let scope = activate_scope(cli.scope.as_ref(), &url)?;
+            Safety::set_safe_mode(!cli.disable_safe_mode);
+
+            let scan_id = uuid::Uuid::new_v4().to_string();
+            let started_at = chrono::Utc::now();
+            Logger::scan_start(&scan_id, "graphql", &url);
+            let mut request = HttpRequest::get(&url);
+            apply_headers(&mut request, &headers, cookie.as_deref());
+
+            let (result, site_map) = GraphQlScanner::new().scan(&request, include_mutations).await;
+            Logger::scan_complete(&url, result.vulnerabilities.len(), result.duration);
+            print_summary(&result);
+
+            let record = ScanRecord {
+                id: scan_id,
+                target: url,
+                module: "graphql".to_string(),
+                started_at,
+                scope: Some(scope),
+                result,
+            };
+            db.save_scan(&record)?;
+            println!("Scan saved with ID: {}", record.id);
+
+            if let Some(site_map) = site_map {
+                for endpoint in &site_map.endpoints {
+                    println!("{:<6} {} [{}]", endpoint.request.method, endpoint.request.url, endpoint.parameters.join(", "));
+                }
+                db.save_site_map(&site_map)?;
+                println!("Site map with {} operations saved with ID: {}", site_map.endpoints.len(), site_map.id);
+            }
+        }
+        Commands::Import { source } => {
+            let site_map = match source {
+                ImportSource::Openapi { file, base_url, auth } => {
+                    let spec = OpenApiSpec::load(&file).map_err(|e| e.to_string())?;
+                    let target = base_url.clone().or_else(|| spec.server_url()).unwrap_or_default();
+                    let mut session = auth.session(&db, &target)?;
+                    let site_map = spec.to_site_map(base_url.as_deref(), &mut session).map_err(|e| e.to_string())?;
+                    db.save_session(&session)?;
+                    println!("Session saved with ID: {}", session.id);
+                    site_map
+                }
+                ImportSource::Har { file, origin, auth } => {
+                    let archive = HarArchive::load(&file).map_err(|e| e.to_string())?;
+                    let origin = origin
+                        .or_else(|| archive.origin())
+                        .ok_or("The HAR file contains no application requests")?;
+                    let mut session = auth.session(&db, &origin)?;
+                    let site_map = archive.to_site_map(&origin, &mut session).map_err(|e| e.to_string())?;
+                    db.save_session(&session)?;
+                    println!("Session saved with ID: {}", session.id);
+                    site_map
+                }
+                ImportSource::Postman { file, auth } => {
+                    let collection = PostmanCollection::load(&file).map_err(|e| e.to_string())?;
+                    let mut session = auth.session(&db, "")?;
+                    let site_map = collection.to_site_map(&mut session).map_err(|e| e.to_string())?;
+                    if session.target.is_empty() {
+                        session.target = site_map.target.clone();
+                    }
+                    db.save_session(&session)?;
+                    println!("Session saved with ID: {}", session.id);
+                    site_map
+                }
+            };
+
+            for endpoint in &site_map.endpoints {
+                println!("{:<6} {} [{}]", endpoint.request.method, endpoint.request.url, endpoint.parameters.join(", "));
+            }
+            db.save_site_map(&site_map)?;
+            println!("Site map with {} endpoints saved with ID: {}", site_map.endpoints.len(), site_map.id);
+        }
+        Commands::SiteMaps => {
+            for site_map in db.list_site_maps()? {
+                println!(
+                    "{}  {}  {} ({} endpoints)",
+                    site_map.id,
+                    site_map.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    site_map.target,
+                    site_map.endpoints.len()
+                );
+            }
+        }
+        Commands::Scans => {
+            for record in db.list_scans()? {
+                println!(
+                    "{}  {}  {:<14} {} ({} findings)",
+                    record.id,
+                    record.started_at.format("%Y-%m-%d %H:%M:%S"),
+                    record.module,
+                    record.target,
+                    record.result.vulnerabilities.len()
+                );
+            }
+        }
+        Commands::Report { scan_id, format, output, template_dir } => {
+            let record = db
+                .get_scan(&scan_id)?
+                .ok_or_else(|| format!("Scan '{}' not found", scan_id))?;
+            let generator = match template_dir {
+                Some(dir) => ReportGenerator::with_template_dir(&dir)?,
+                None => ReportGenerator::new(),
+            };
+
+            std::fs::create_dir_all(&output)?;
+            for report_format in format.formats() {
+                let rendered = generator.render(&record, report_format)?;
+                let path = output.join(format!("redlines-report-{}.{}", record.id, report_format.extension()));
+                std::fs::write(&path, rendered)?;
+                println!("Report written to {}", path.display());
+            }
+        }
+        Commands::Audit { since, until, scan_id, format, output } => {
+            let entries = AuditLog::read_entries(&cli.audit_log, since, until, scan_id.as_deref())?;
+            let exported = match format {
+                AuditFormatArg::Jsonl => entries
+                    .iter()
+                    .map(|entry| serde_json::to_string(entry).map(|line| line + "\n"))
+                    .collect::<Result<String, _>>()?,
+                AuditFormatArg::Csv => AuditLog::to_csv(&entries),
+            };
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, exported)?;
+                    println!("Exported {} audit entries to {}", entries.len(), path.display());
+                }
+                None => print!("{}", exported),
+            }
         }
         Commands::List => {
-            println!("Available modules:\n - sql_injection");
+            println!("Available modules:");
+            for info in module_handler.get_module_info() {
+                println!(" - {}: {}", info.name, info.description);
+            }
         }
         Commands::Version => {
             println!("Redlines X version 0.1");
         }
     }
+
+    Ok(ExitCode::from(EXIT_SUCCESS))
 }
@@ -0,0 +1,246 @@
+// src/core/audit.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+static AUDIT_LOG: Mutex<Option<AuditLog>> = Mutex::new(None);
+
+/// Headers carrying credentials; only a fingerprint of their value is recorded
+const CREDENTIAL_HEADERS: [&str; 9] = [
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "apikey",
+    "x-auth-token",
+    "x-access-token",
+    "x-csrf-token",
+    "x-xsrf-token",
+];
+
+/// A single HTTP request sent by the scanner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the request was sent
+    pub timestamp: DateTime<Utc>,
+    pub scan_id: Option<String>,
+    pub module: Option<String>,
+    /// Why the request was sent, e.g. "sqli-error-probe"
+    pub purpose: String,
+    pub method: String,
+    pub url: String,
+    /// URL whose redirect response led to this request, if it is a redirect hop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirected_from: Option<String>,
+    /// Headers as sent; credential and cookie values are replaced by their
+    /// SHA-256 fingerprint, keeping the cookie names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Body as sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Response status, if a response was received
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+    /// Transport error, if the request failed
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Headers in the form they are recorded, sorted by name
+    pub fn recorded_headers(headers: &HashMap<String, String>) -> BTreeMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let lower = name.to_ascii_lowercase();
+                let value = if CREDENTIAL_HEADERS.contains(&lower.as_str()) {
+                    fingerprint(value)
+                } else if lower == "cookie" {
+                    value
+                        .split(';')
+                        .map(|cookie| match cookie.trim().split_once('=') {
+                            Some((name, value)) => format!("{}={}", name, fingerprint(value)),
+                            None => fingerprint(cookie.trim()),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                } else {
+                    value.clone()
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// SHA-256 fingerprint recorded in place of a secret value
+fn fingerprint(value: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(value.as_bytes()))
+}
+
+/// Append-only JSON Lines audit trail of every request sent by `RequestEngine`
+pub struct AuditLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl AuditLog {
+    /// Opens the audit log at `path` for appending, creating it if needed
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { path: path.to_path_buf(), file })
+    }
+
+    /// Installs the audit log used for all requests in this process
+    pub fn init(path: &Path) -> io::Result<()> {
+        *AUDIT_LOG.lock().unwrap() = Some(Self::open(path)?);
+        Ok(())
+    }
+
+    /// Appends an entry to the process-wide audit log, if one was installed
+    pub fn record(entry: &AuditEntry) {
+        if let Some(audit) = AUDIT_LOG.lock().unwrap().as_mut()
+            && let Err(e) = audit.append(entry)
+        {
+            log::error!("Failed to write audit entry to {}: {}", audit.path.display(), e);
+        }
+    }
+
+    /// Appends an entry as one JSON line
+    pub fn append(&mut self, entry: &AuditEntry) -> io::Result<()> {
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()
+    }
+
+    /// Reads entries from an audit log file, keeping those sent within
+    /// `[since, until)` and, if given, belonging to `scan_id`
+    pub fn read_entries(
+        path: &Path,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        scan_id: Option<&str>,
+    ) -> io::Result<Vec<AuditEntry>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut entries = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: AuditEntry = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", index + 1, e))
+            })?;
+
+            if since.is_some_and(|since| entry.timestamp < since)
+                || until.is_some_and(|until| entry.timestamp >= until)
+                || scan_id.is_some_and(|id| entry.scan_id.as_deref() != Some(id))
+            {
+                continue;
+            }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Formats entries as CSV with a header row
+    pub fn to_csv(entries: &[AuditEntry]) -> String {
+        let mut csv = String::from("timestamp,scan_id,module,purpose,method,url,status,duration_ms,error,headers,body,redirected_from\n");
+        for entry in entries {
+            let fields = [
+                entry.timestamp.to_rfc3339(),
+                entry.scan_id.clone().unwrap_or_default(),
+                entry.module.clone().unwrap_or_default(),
+                entry.purpose.clone(),
+                entry.method.clone(),
+                entry.url.clone(),
+                entry.status.map(|s| s.to_string()).unwrap_or_default(),
+                entry.duration_ms.to_string(),
+                entry.error.clone().unwrap_or_default(),
+                entry.headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect::<Vec<_>>().join("\n"),
+                entry.body.clone().unwrap_or_default(),
+                entry.redirected_from.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quotes a CSV field when it contains separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, scan_id: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
+            scan_id: Some(scan_id.to_string()),
+            module: Some("sql_injection".to_string()),
+            purpose: "sqli-error-probe".to_string(),
+            method: "GET".to_string(),
+            url: "http://example.com/?id=1,2".to_string(),
+            redirected_from: None,
+            headers: BTreeMap::new(),
+            body: None,
+            status: Some(200),
+            duration_ms: 12,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_read_entries_filters_by_window_and_scan() {
+        let path = std::env::temp_dir().join(format!("redlines-audit-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut audit = AuditLog::open(&path).unwrap();
+        audit.append(&entry("2026-01-01T10:00:00Z", "a")).unwrap();
+        audit.append(&entry("2026-01-02T10:00:00Z", "a")).unwrap();
+        audit.append(&entry("2026-01-02T11:00:00Z", "b")).unwrap();
+
+        let since = DateTime::parse_from_rfc3339("2026-01-02T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(AuditLog::read_entries(&path, Some(since), None, None).unwrap().len(), 2);
+        assert_eq!(AuditLog::read_entries(&path, Some(since), None, Some("a")).unwrap().len(), 1);
+        assert_eq!(AuditLog::read_entries(&path, None, Some(since), None).unwrap().len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let mut sent = entry("2026-01-01T10:00:00Z", "a");
+        let headers = HashMap::from([
+            ("Authorization".to_string(), "Bearer secret".to_string()),
+            ("X-Api-Key".to_string(), "key-secret".to_string()),
+            ("Cookie".to_string(), "session=secret; theme=dark".to_string()),
+            ("X-Forwarded-For".to_string(), "1' AND 1=1--".to_string()),
+        ]);
+        sent.headers = AuditEntry::recorded_headers(&headers);
+        sent.body = Some("id=1'".to_string());
+        assert!(sent.headers["Authorization"].starts_with("sha256:") && !sent.headers["Authorization"].contains("secret"));
+        assert!(sent.headers["X-Api-Key"].starts_with("sha256:"));
+        let cookie = &sent.headers["Cookie"];
+        assert!(cookie.starts_with("session=sha256:") && cookie.contains("; theme=sha256:") && !cookie.contains("secret"));
+
+        let csv = AuditLog::to_csv(&[sent]);
+        assert!(csv.contains(",\"http://example.com/?id=1,2\",200,12,"));
+        assert!(csv.contains("X-Forwarded-For: 1' AND 1=1--\",id=1',\n"));
+    }
+}
@@ -1,7 +1,12 @@
 // src/core/database.rs
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
+use crate::core::scan_result::{ScanRecord, ScanResult};
 use crate::core::session::Session;
+use crate::core::site_map::SiteMap;
+use chrono::{DateTime, Utc};
 use serde_json;
+use std::collections::HashMap;
+use std::time::Duration;
 
 pub struct Database {
     conn: Connection,
@@ -9,7 +14,12 @@ pub struct Database {
 
 impl Database {
     pub fn new() -> Result<Self> {
-        let conn = Connection::open("redlines.db")?;
+        Self::open("redlines.db")
+    }
+
+    /// Opens (or creates) the database at the given path and ensures the schema exists
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS sessions (
@@ -23,6 +33,34 @@ impl Database {
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scans (
+                id TEXT PRIMARY KEY,
+                target TEXT NOT NULL,
+                module TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                vulnerabilities TEXT NOT NULL,
+                warnings TEXT NOT NULL,
+                scope TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "scans", "scope", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS site_maps (
+                id TEXT PRIMARY KEY,
+                target TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                endpoints TEXT NOT NULL,
+                session TEXT
+            )",
+            [],
+        )?;
+        add_column_if_missing(&conn, "site_maps", "session", "TEXT")?;
+
         Ok(Self { conn })
     }
     
@@ -42,4 +80,231 @@ impl Database {
 
         Ok(())
     }
+
+    /// Loads a stored session by id
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        self.conn
+            .query_row(
+                "SELECT id, target, cookies, headers, variables, created_at FROM sessions WHERE id = ?1",
+                [id],
+                |row| {
+                    let json = |index: usize| -> Result<HashMap<String, String>> {
+                        let text: Option<String> = row.get(index)?;
+                        text.map(|text| serde_json::from_str(&text).map_err(|e| conversion_error(index, e)))
+                            .transpose()
+                            .map(|map| map.unwrap_or_default())
+                    };
+                    let created_at: Option<String> = row.get(5)?;
+                    Ok(Session {
+                        id: row.get(0)?,
+                        target: row.get(1)?,
+                        cookies: json(2)?,
+                        headers: json(3)?,
+                        variables: json(4)?,
+                        created_at: created_at
+                            .map(|text| DateTime::parse_from_rfc3339(&text).map(|dt| dt.with_timezone(&Utc)))
+                            .transpose()
+                            .map_err(|e| conversion_error(5, e))?
+                            .unwrap_or_else(Utc::now),
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Persists a completed scan so it can be reported on later
+    pub fn save_scan(&self, record: &ScanRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO scans
+                (id, target, module, started_at, success, duration_ms, vulnerabilities, warnings, scope)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                record.id,
+                record.target,
+                record.module,
+                record.started_at.to_rfc3339(),
+                record.result.success,
+                record.result.duration.as_millis() as i64,
+                serde_json::to_string(&record.result.vulnerabilities).unwrap(),
+                serde_json::to_string(&record.result.warnings).unwrap(),
+                record.scope.as_ref().map(|scope| serde_json::to_string(scope).unwrap()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Loads a stored scan by id
+    pub fn get_scan(&self, id: &str) -> Result<Option<ScanRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, target, module, started_at, success, duration_ms, vulnerabilities, warnings, scope
+                 FROM scans WHERE id = ?1",
+                [id],
+                scan_from_row,
+            )
+            .optional()
+    }
+
+    /// Lists all stored scans, most recent first
+    pub fn list_scans(&self) -> Result<Vec<ScanRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, target, module, started_at, success, duration_ms, vulnerabilities, warnings, scope
+             FROM scans ORDER BY started_at DESC",
+        )?;
+        let rows = stmt.query_map([], scan_from_row)?;
+        rows.collect()
+    }
+
+    /// Persists a site map so later scans can use it as input
+    pub fn save_site_map(&self, site_map: &SiteMap) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO site_maps (id, target, created_at, endpoints, session) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                site_map.id,
+                site_map.target,
+                site_map.created_at.to_rfc3339(),
+                serde_json::to_string(&site_map.endpoints).unwrap(),
+                site_map.session,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Loads a stored site map by id
+    pub fn get_site_map(&self, id: &str) -> Result<Option<SiteMap>> {
+        self.conn
+            .query_row(
+                "SELECT id, target, created_at, endpoints, session FROM site_maps WHERE id = ?1",
+                [id],
+                site_map_from_row,
+            )
+            .optional()
+    }
+
+    /// Lists all stored site maps, most recent first
+    pub fn list_site_maps(&self) -> Result<Vec<SiteMap>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, target, created_at, endpoints, session FROM site_maps ORDER BY created_at DESC")?;
+        let rows = stmt.query_map([], site_map_from_row)?;
+        rows.collect()
+    }
+}
+
+/// Maps a row of the `site_maps` table back into a `SiteMap`
+fn site_map_from_row(row: &Row) -> Result<SiteMap> {
+    let created_at: String = row.get(2)?;
+    let endpoints: String = row.get(3)?;
+
+    let mut site_map = SiteMap::new(&row.get::<_, String>(1)?);
+    site_map.id = row.get(0)?;
+    site_map.created_at = DateTime::parse_from_rfc3339(&created_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| conversion_error(2, e))?;
+    site_map.endpoints = serde_json::from_str(&endpoints).map_err(|e| conversion_error(3, e))?;
+    site_map.session = row.get(4)?;
+    Ok(site_map)
+}
+
+/// Maps a row of the `scans` table back into a `ScanRecord`
+fn scan_from_row(row: &Row) -> Result<ScanRecord> {
+    let started_at: String = row.get(3)?;
+    let vulnerabilities: String = row.get(6)?;
+    let warnings: String = row.get(7)?;
+    let scope: Option<String> = row.get(8)?;
+
+    Ok(ScanRecord {
+        id: row.get(0)?,
+        target: row.get(1)?,
+        module: row.get(2)?,
+        started_at: DateTime::parse_from_rfc3339(&started_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| conversion_error(3, e))?,
+        scope: scope
+            .map(|scope| serde_json::from_str(&scope))
+            .transpose()
+            .map_err(|e| conversion_error(8, e))?,
+        result: ScanResult {
+            success: row.get(4)?,
+            duration: Duration::from_millis(row.get::<_, i64>(5)? as u64),
+            vulnerabilities: serde_json::from_str(&vulnerabilities).map_err(|e| conversion_error(6, e))?,
+            warnings: serde_json::from_str(&warnings).map_err(|e| conversion_error(7, e))?,
+        },
+    })
+}
+
+/// Adds a column to an existing table created by an older version of the schema
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+fn conversion_error(
+    column: usize,
+    error: impl std::error::Error + Send + Sync + 'static,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::http_request::HttpRequest;
+    use crate::core::scope::Scope;
+    use crate::core::site_map::{Endpoint, EndpointSource};
+
+    #[test]
+    fn test_scan_round_trip() {
+        let db = Database::open(":memory:").unwrap();
+        let mut record = ScanRecord::new(
+            "http://example.com",
+            "sql_injection",
+            Utc::now(),
+            ScanResult {
+                success: false,
+                vulnerabilities: Vec::new(),
+                warnings: vec!["No SQL Injection vulnerabilities detected".to_string()],
+                duration: Duration::from_millis(1250),
+            },
+        );
+        record.scope = Some(Scope::for_target("http://example.com").unwrap());
+        db.save_scan(&record).unwrap();
+
+        let loaded = db.get_scan(&record.id).unwrap().unwrap();
+        assert_eq!(loaded.target, "http://example.com");
+        assert_eq!(loaded.scope, record.scope);
+        assert_eq!(loaded.result.duration, Duration::from_millis(1250));
+        assert_eq!(loaded.result.warnings, record.result.warnings);
+        assert!(db.get_scan("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_site_map_round_trip() {
+        let db = Database::open(":memory:").unwrap();
+        let mut site_map = SiteMap::new("http://example.com/");
+        let mut endpoint = Endpoint::new(HttpRequest::get("http://example.com/item?id=3"), EndpointSource::Link, 1);
+        endpoint.status = Some(200);
+        site_map.add(endpoint);
+        site_map.session = Some("s-1".to_string());
+        db.save_site_map(&site_map).unwrap();
+
+        let mut loaded = db.get_site_map(&site_map.id).unwrap().unwrap();
+        assert_eq!(loaded.target, "http://example.com/");
+        assert_eq!(loaded.endpoints, site_map.endpoints);
+        assert_eq!(loaded.endpoints[0].parameters, vec!["id"]);
+        assert_eq!(loaded.session.as_deref(), Some("s-1"));
+        assert!(!loaded.add(Endpoint::new(HttpRequest::get("http://example.com/item?id=4"), EndpointSource::Link, 2)));
+        assert_eq!(db.list_site_maps().unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,255 @@
+// src/core/encoding.rs
+
+use crate::core::injection::InjectionLocation;
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+
+/// Everything but RFC 3986 unreserved characters is escaped
+pub(crate) const URL_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// Runs of `\uXXXX` escapes, decoded together so surrogate pairs survive
+static UNICODE_ESCAPES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\\u[0-9a-fA-F]{4})+").expect("unicode escape pattern is valid"));
+
+/// Chain selected for every injection point of the current scan, applied ahead
+/// of each location's encoding
+static SELECTED: RwLock<Option<EncodingPipeline>> = RwLock::new(None);
+
+/// A single encoding layer between a payload and the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoder {
+    /// Percent-encoding of everything but unreserved characters
+    Url,
+    /// Percent-encoding applied twice, for values decoded twice
+    DoubleUrl,
+    /// HTML/XML entities for `& < > " '`
+    Html,
+    /// `\uXXXX` escapes for everything but ASCII letters and digits
+    Unicode,
+    /// Standard base64
+    Base64,
+    /// Escaping for the inside of a JSON string
+    Json,
+}
+
+impl Encoder {
+    pub const ALL: [Encoder; 6] =
+        [Encoder::Url, Encoder::DoubleUrl, Encoder::Html, Encoder::Unicode, Encoder::Base64, Encoder::Json];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoder::Url => "url",
+            Encoder::DoubleUrl => "double-url",
+            Encoder::Html => "html",
+            Encoder::Unicode => "unicode",
+            Encoder::Base64 => "base64",
+            Encoder::Json => "json",
+        }
+    }
+
+    pub fn encode(&self, value: &str) -> String {
+        match self {
+            Encoder::Url => utf8_percent_encode(value, URL_COMPONENT).to_string(),
+            Encoder::DoubleUrl => Encoder::Url.encode(&Encoder::Url.encode(value)),
+            Encoder::Html => value
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&apos;"),
+            Encoder::Unicode => value
+                .encode_utf16()
+                .map(|unit| match char::from_u32(unit as u32) {
+                    Some(c) if c.is_ascii_alphanumeric() => c.to_string(),
+                    _ => format!("\\u{:04x}", unit),
+                })
+                .collect(),
+            Encoder::Base64 => base64::engine::general_purpose::STANDARD.encode(value),
+            Encoder::Json => {
+                let quoted = serde_json::Value::String(value.to_string()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+        }
+    }
+
+    /// Reverses `encode` as far as possible; text that is not valid for the
+    /// encoding is returned unchanged
+    pub fn decode(&self, text: &str) -> String {
+        match self {
+            Encoder::Url => percent_decode_str(text).decode_utf8_lossy().into_owned(),
+            Encoder::DoubleUrl => Encoder::Url.decode(&Encoder::Url.decode(text)),
+            Encoder::Html => text
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&#39;", "'")
+                .replace("&amp;", "&"),
+            Encoder::Unicode => UNICODE_ESCAPES
+                .replace_all(text, |captures: &regex::Captures| {
+                    let units: Vec<u16> = captures[0]
+                        .split("\\u")
+                        .filter_map(|hex| u16::from_str_radix(hex, 16).ok())
+                        .collect();
+                    String::from_utf16_lossy(&units)
+                })
+                .into_owned(),
+            Encoder::Json => serde_json::from_str(&format!("\"{}\"", text)).unwrap_or_else(|_| text.to_string()),
+            Encoder::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .unwrap_or_else(|| text.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Encoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Encoder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase().replace('_', "-");
+        Encoder::ALL.into_iter().find(|e| e.as_str() == name).ok_or_else(|| {
+            let known: Vec<&str> = Encoder::ALL.iter().map(Encoder::as_str).collect();
+            format!("unknown encoding '{}' (expected {})", s, known.join(", "))
+        })
+    }
+}
+
+/// Encoders applied in order to a payload before it is written into a request;
+/// the last one matches the syntax around the injection point
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EncodingPipeline {
+    encoders: Vec<Encoder>,
+}
+
+impl EncodingPipeline {
+    /// A pipeline that leaves payloads unchanged
+    pub fn raw() -> Self {
+        Self::default()
+    }
+
+    pub fn new(encoders: Vec<Encoder>) -> Self {
+        Self { encoders }
+    }
+
+    /// Adds an encoder after the current ones
+    pub fn then(mut self, encoder: Encoder) -> Self {
+        self.encoders.push(encoder);
+        self
+    }
+
+    pub fn encoders(&self) -> &[Encoder] {
+        &self.encoders
+    }
+
+    pub fn encode(&self, value: &str) -> String {
+        self.encoders.iter().fold(value.to_string(), |text, encoder| encoder.encode(&text))
+    }
+
+    /// Reverses `encode`, last encoder first
+    pub fn decode(&self, text: &str) -> String {
+        self.encoders.iter().rev().fold(text.to_string(), |value, encoder| encoder.decode(&value))
+    }
+
+    /// The encoding a value needs at a location to arrive unchanged: URL-encoding
+    /// in the query, path, form bodies and cookies, escaping in JSON strings,
+    /// entities in XML and none in headers and multipart fields
+    pub fn for_location(location: InjectionLocation) -> Self {
+        match location {
+            InjectionLocation::Query
+            | InjectionLocation::PathSegment
+            | InjectionLocation::FormField
+            | InjectionLocation::Cookie => Self::raw().then(Encoder::Url),
+            InjectionLocation::JsonPath => Self::raw().then(Encoder::Json),
+            InjectionLocation::XmlNode => Self::raw().then(Encoder::Html),
+            InjectionLocation::Header | InjectionLocation::MultipartField => Self::raw(),
+        }
+    }
+
+    /// This chain followed by the encoding `location` needs, so that the value
+    /// survives the location's own decoding first
+    pub fn within(mut self, location: InjectionLocation) -> Self {
+        self.encoders.extend(Self::for_location(location).encoders);
+        self
+    }
+
+    /// Selects the chain applied at every injection point of the current scan
+    /// (the `--encode` option), ahead of each location's encoding; `None`
+    /// restores the location encoding alone
+    pub fn select(pipeline: Option<EncodingPipeline>) {
+        *SELECTED.write().unwrap() = pipeline;
+    }
+
+    /// The pipeline selected with `select`, if any
+    pub fn selected() -> Option<EncodingPipeline> {
+        SELECTED.read().unwrap().clone()
+    }
+}
+
+impl fmt::Display for EncodingPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.encoders.is_empty() {
+            return f.write_str("none");
+        }
+        let names: Vec<&str> = self.encoders.iter().map(Encoder::as_str).collect();
+        f.write_str(&names.join(","))
+    }
+}
+
+/// Parses a comma-separated chain such as `base64,url`; `none` is the raw pipeline
+impl FromStr for EncodingPipeline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("none") {
+            return Ok(Self::raw());
+        }
+        let encoders = s.split(',').map(str::parse).collect::<Result<Vec<Encoder>, _>>()?;
+        Ok(Self::new(encoders))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoders_round_trip() {
+        let payload = "1' AND \"a\"<>'b'--é";
+        for encoder in Encoder::ALL {
+            assert_eq!(encoder.decode(&encoder.encode(payload)), payload, "{}", encoder);
+        }
+        assert_eq!(Encoder::Url.encode("a b'"), "a%20b%27");
+        assert_eq!(Encoder::DoubleUrl.encode("'"), "%2527");
+        assert_eq!(Encoder::Unicode.encode("1'"), "1\\u0027");
+        assert_eq!(Encoder::Json.encode("a\"\\"), "a\\\"\\\\");
+    }
+
+    #[test]
+    fn test_pipeline_chains_in_order() {
+        let pipeline: EncodingPipeline = "base64, url".parse().unwrap();
+        assert_eq!(pipeline, EncodingPipeline::raw().then(Encoder::Base64).then(Encoder::Url));
+        assert_eq!(pipeline.encode("1'>"), "MSc%2B");
+        assert_eq!(pipeline.to_string(), "base64,url");
+        assert_eq!("none".parse::<EncodingPipeline>().unwrap().encode("1'"), "1'");
+        assert!("url,rot13".parse::<EncodingPipeline>().unwrap_err().contains("rot13"));
+
+        assert_eq!("base64".parse::<EncodingPipeline>().unwrap().within(InjectionLocation::Query).encode("1'>"), "MSc%2B");
+        assert_eq!(EncodingPipeline::raw().within(InjectionLocation::JsonPath).encode("1\"<"), "1\\\"<");
+        assert_eq!(EncodingPipeline::raw().within(InjectionLocation::XmlNode).encode("1<"), "1&lt;");
+    }
+}
@@ -0,0 +1,182 @@
+// src/core/http_request.rs
+
+use crate::core::safety::RiskLevel;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+/// Methods that only read; any other method may change data on the server
+const READING_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS"];
+
+/// A `mutation` operation definition in a GraphQL document
+static GRAPHQL_MUTATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\A|\})\s*mutation\b").expect("mutation pattern is valid"));
+
+/// How a request body is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    Form,
+    Json,
+    Xml,
+    Multipart,
+    Other,
+}
+
+/// A concrete HTTP request that modules can replay and inject into
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Creates a GET request without headers or body
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: HashMap::new(),
+            body: None,
+        }
+    }
+
+    /// Returns a header value, matching the name case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a header, replacing any existing one with the same name in any case
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.retain(|key, _| !key.eq_ignore_ascii_case(name));
+        self.headers.insert(name.to_string(), value.to_string());
+    }
+
+    /// Cookies sent in the `Cookie` header, in order
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        self.header("Cookie")
+            .map(|header| {
+                header
+                    .split(';')
+                    .filter_map(|pair| {
+                        let (name, value) = pair.trim().split_once('=')?;
+                        Some((name.trim().to_string(), value.trim().to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Detects the body encoding from the `Content-Type` header, falling back to
+    /// sniffing the body itself
+    pub fn body_kind(&self) -> Option<BodyKind> {
+        let body = self.body.as_deref()?;
+        let content_type = self.header("Content-Type").unwrap_or("").to_ascii_lowercase();
+
+        let kind = if content_type.contains("json") {
+            BodyKind::Json
+        } else if content_type.contains("xml") {
+            BodyKind::Xml
+        } else if content_type.contains("x-www-form-urlencoded") {
+            BodyKind::Form
+        } else if content_type.contains("multipart/form-data") {
+            BodyKind::Multipart
+        } else if !content_type.is_empty() {
+            BodyKind::Other
+        } else {
+            let trimmed = body.trim_start();
+            if trimmed.starts_with('{') || trimmed.starts_with('[') {
+                BodyKind::Json
+            } else if trimmed.starts_with('<') {
+                BodyKind::Xml
+            } else if body.contains('=') {
+                BodyKind::Form
+            } else {
+                BodyKind::Other
+            }
+        };
+        Some(kind)
+    }
+
+    /// Sets a `multipart/form-data` body with one text part per field
+    pub fn set_multipart_body(&mut self, fields: &[(String, String)]) {
+        let boundary = format!("redlines-{}", uuid::Uuid::new_v4().simple());
+        let mut body = String::new();
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary,
+                name.replace('"', "%22"),
+                value
+            ));
+        }
+        body.push_str(&format!("--{}--\r\n", boundary));
+        self.set_header("Content-Type", &format!("multipart/form-data; boundary={}", boundary));
+        self.body = Some(body);
+    }
+
+    /// Boundary of a `multipart/form-data` body, from the `Content-Type` header
+    pub fn multipart_boundary(&self) -> Option<String> {
+        let content_type = self.header("Content-Type")?;
+        content_type.split(';').skip(1).find_map(|parameter| {
+            let (name, value) = parameter.trim().split_once('=')?;
+            let value = value.trim().trim_matches('"');
+            (name.trim().eq_ignore_ascii_case("boundary") && !value.is_empty()).then(|| value.to_string())
+        })
+    }
+
+    /// Returns true if the body is a GraphQL request: a JSON object with a
+    /// `query` string
+    pub fn is_graphql(&self) -> bool {
+        self.graphql_query().is_some()
+    }
+
+    /// The `query` string of a GraphQL request body
+    fn graphql_query(&self) -> Option<String> {
+        if self.body_kind() != Some(BodyKind::Json) {
+            return None;
+        }
+        let json: serde_json::Value = serde_json::from_str(self.body.as_deref()?).ok()?;
+        json.get("query")?.as_str().map(str::to_string)
+    }
+
+    /// Risk of sending the request at all, whatever is injected into it
+    ///
+    /// GET, HEAD and OPTIONS requests and GraphQL queries only read. Any other
+    /// method, like a submitted form or a GraphQL mutation, may change data on
+    /// the server each time it is replayed.
+    pub fn risk(&self) -> RiskLevel {
+        if READING_METHODS.iter().any(|method| self.method.eq_ignore_ascii_case(method)) {
+            return RiskLevel::ReadOnly;
+        }
+        match self.graphql_query() {
+            Some(query) if !GRAPHQL_MUTATION.is_match(query.trim()) => RiskLevel::ReadOnly,
+            _ => RiskLevel::StateChanging,
+        }
+    }
+}
+
+/// Renders the request in a raw HTTP-like form for evidence
+impl fmt::Display for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+
+        let mut headers: Vec<_> = self.headers.iter().collect();
+        headers.sort();
+        for (name, value) in headers {
+            write!(f, "\n{}: {}", name, value)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+        Ok(())
+    }
+}
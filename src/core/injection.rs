@@ -0,0 +1,544 @@
+// src/core/injection.rs
+
+use crate::core::encoding::{Encoder, EncodingPipeline};
+use crate::core::http_request::{BodyKind, HttpRequest};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fmt;
+use std::ops::Range;
+use url::{form_urlencoded, Url};
+
+/// Headers that carry transport details rather than application input
+const SKIPPED_HEADERS: &[&str] = &[
+    "host", "content-length", "content-type", "cookie", "connection", "accept", "accept-encoding",
+    "accept-language", "transfer-encoding",
+];
+
+/// Where in a request an injected value is placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InjectionLocation {
+    Query,
+    FormField,
+    JsonPath,
+    XmlNode,
+    Header,
+    Cookie,
+    PathSegment,
+    MultipartField,
+}
+
+impl fmt::Display for InjectionLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InjectionLocation::Query => "query parameter",
+            InjectionLocation::FormField => "form field",
+            InjectionLocation::JsonPath => "JSON field",
+            InjectionLocation::XmlNode => "XML node",
+            InjectionLocation::Header => "header",
+            InjectionLocation::Cookie => "cookie",
+            InjectionLocation::PathSegment => "path segment",
+            InjectionLocation::MultipartField => "multipart field",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum JsonStep {
+    Key(String),
+    Index(usize),
+}
+
+/// Location details needed to rewrite the request, beyond the point's name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum Locator {
+    Name,
+    Json(Vec<JsonStep>),
+    Xml(Range<usize>),
+    Segment(usize),
+    Multipart(Range<usize>),
+}
+
+/// A single place in a request where a payload can be injected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InjectionPoint {
+    pub location: InjectionLocation,
+    /// Parameter, field, header or cookie name; `$.a.b[0]` for JSON and
+    /// `/a/b` for XML; for path segments the parameter name if known, else
+    /// the original value
+    pub name: String,
+    /// Value present in the original request
+    pub original: String,
+    /// Encoders applied to injected values; `None` picks them from the location
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<EncodingPipeline>,
+    locator: Locator,
+}
+
+impl InjectionPoint {
+    /// A query string parameter, which need not exist in the base request yet
+    pub fn query(name: &str, original: &str) -> Self {
+        Self::named(InjectionLocation::Query, name, original)
+    }
+
+    /// A request header, which need not exist in the base request yet
+    pub fn header(name: &str, original: &str) -> Self {
+        Self::named(InjectionLocation::Header, name, original)
+    }
+
+    /// The path segment at `index` (0 is the first segment after the host),
+    /// e.g. a templated path parameter such as `{name}` in `/users/{name}`
+    pub fn path_segment(index: usize, name: &str, original: &str) -> Self {
+        Self {
+            location: InjectionLocation::PathSegment,
+            name: name.to_string(),
+            original: original.to_string(),
+            encoding: None,
+            locator: Locator::Segment(index),
+        }
+    }
+
+    /// Returns true if both points rewrite the same value of a request
+    pub fn same_target(&self, other: &InjectionPoint) -> bool {
+        self.location == other.location
+            && match (&self.locator, &other.locator) {
+                (Locator::Name, Locator::Name) if self.location == InjectionLocation::Header => {
+                    self.name.eq_ignore_ascii_case(&other.name)
+                }
+                (Locator::Name, Locator::Name) => self.name == other.name,
+                (a, b) => a == b,
+            }
+    }
+
+    /// Sets the encoders applied to injected values at this point
+    pub fn with_encoding(mut self, encoding: EncodingPipeline) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Encoders applied to injected values: the point's own, else the chain
+    /// selected for the scan followed by the encoding its location needs
+    pub fn pipeline(&self) -> EncodingPipeline {
+        self.encoding
+            .clone()
+            .unwrap_or_else(|| EncodingPipeline::selected().unwrap_or_default().within(self.location))
+    }
+
+    /// The original value as the application sees it. `original` is decoded
+    /// for the location only; a different pipeline decodes it further, e.g. a
+    /// base64 parameter yields its decoded text.
+    pub fn original_value(&self) -> String {
+        let automatic = EncodingPipeline::for_location(self.location);
+        let pipeline = self.pipeline();
+        if pipeline == automatic {
+            return self.original.clone();
+        }
+        pipeline.decode(&automatic.encode(&self.original))
+    }
+
+    fn named(location: InjectionLocation, name: &str, original: &str) -> Self {
+        Self {
+            location,
+            name: name.to_string(),
+            original: original.to_string(),
+            encoding: None,
+            locator: Locator::Name,
+        }
+    }
+
+    /// Finds every injectable value in a request: query parameters, id-like path
+    /// segments, form/JSON/XML body values, cookies and application headers
+    pub fn discover(request: &HttpRequest) -> Vec<InjectionPoint> {
+        let mut points = Vec::new();
+
+        if let Ok(url) = Url::parse(&request.url) {
+            for (name, value) in url.query_pairs() {
+                if !points.iter().any(|p: &InjectionPoint| p.location == InjectionLocation::Query && p.name == name) {
+                    points.push(Self::query(&name, &value));
+                }
+            }
+
+            if let Some(segments) = url.path_segments() {
+                for (index, segment) in segments.enumerate() {
+                    if is_value_segment(segment) {
+                        points.push(Self::path_segment(index, segment, segment));
+                    }
+                }
+            }
+        }
+
+        if let (Some(body), Some(kind)) = (request.body.as_deref(), request.body_kind()) {
+            match kind {
+                BodyKind::Form => {
+                    for (name, value) in form_urlencoded::parse(body.as_bytes()) {
+                        points.push(Self::named(InjectionLocation::FormField, &name, &value));
+                    }
+                }
+                BodyKind::Json => {
+                    // The query of a GraphQL request is code; its arguments are in `variables`
+                    match serde_json::from_str::<JsonValue>(body) {
+                        Ok(json) if request.is_graphql() => {
+                            if let Some(variables) = json.get("variables") {
+                                let mut path = vec![JsonStep::Key("variables".to_string())];
+                                collect_json_points(variables, &mut path, &mut points);
+                            }
+                        }
+                        Ok(json) => collect_json_points(&json, &mut Vec::new(), &mut points),
+                        Err(_) => {}
+                    }
+                }
+                BodyKind::Xml => collect_xml_points(body, &mut points),
+                BodyKind::Multipart => {
+                    if let Some(boundary) = request.multipart_boundary() {
+                        collect_multipart_points(body, &boundary, &mut points);
+                    }
+                }
+                BodyKind::Other => {}
+            }
+        }
+
+        for (name, value) in request.cookies() {
+            points.push(Self::named(InjectionLocation::Cookie, &name, &value));
+        }
+
+        let mut headers: Vec<_> = request
+            .headers
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+            .collect();
+        headers.sort();
+        for (name, value) in headers {
+            points.push(Self::header(name, value));
+        }
+
+        points
+    }
+
+    /// Returns a copy of `base` with this point's value replaced by `value`,
+    /// encoded with the point's pipeline
+    pub fn inject(&self, base: &HttpRequest, value: &str) -> HttpRequest {
+        let mut request = base.clone();
+        let encoded = self.pipeline().encode(value);
+
+        match (&self.location, &self.locator) {
+            (InjectionLocation::Query, _) => {
+                if let Ok(mut url) = Url::parse(&base.url) {
+                    url.set_query(Some(&replace_pair_value(url.query().unwrap_or(""), &self.name, &encoded)));
+                    request.url = url.to_string();
+                }
+            }
+            (InjectionLocation::FormField, _) => {
+                request.body = Some(replace_pair_value(base.body.as_deref().unwrap_or(""), &self.name, &encoded));
+            }
+            (InjectionLocation::JsonPath, Locator::Json(steps)) => {
+                if let Some(mut json) = base.body.as_deref().and_then(|b| serde_json::from_str::<JsonValue>(b).ok()) {
+                    // Serialize around a marker so the encoded text is written as is
+                    let marker = format!("rlx-{}", uuid::Uuid::new_v4().simple());
+                    if let Some(target) = json_pointer_mut(&mut json, steps) {
+                        *target = JsonValue::String(marker.clone());
+                    }
+                    request.body = Some(json.to_string().replacen(&marker, &encoded, 1));
+                }
+            }
+            (InjectionLocation::XmlNode, Locator::Xml(range)) | (InjectionLocation::MultipartField, Locator::Multipart(range)) => {
+                if let Some(body) = base.body.as_deref().filter(|b| b.len() >= range.end) {
+                    request.body = Some(format!("{}{}{}", &body[..range.start], encoded, &body[range.end..]));
+                }
+            }
+            (InjectionLocation::Header, _) => request.set_header(&self.name, &encoded),
+            (InjectionLocation::Cookie, _) => {
+                let mut cookies = base.cookies();
+                match cookies.iter_mut().find(|(name, _)| *name == self.name) {
+                    Some(cookie) => cookie.1 = encoded,
+                    None => cookies.push((self.name.clone(), encoded)),
+                }
+                let header: Vec<String> = cookies.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+                request.set_header("Cookie", &header.join("; "));
+            }
+            (InjectionLocation::PathSegment, Locator::Segment(index)) => {
+                if let Ok(mut url) = Url::parse(&base.url) {
+                    let mut segments: Vec<String> = url
+                        .path_segments()
+                        .map(|s| s.map(|seg| seg.to_string()).collect())
+                        .unwrap_or_default();
+                    if let Some(segment) = segments.get_mut(*index) {
+                        *segment = encoded;
+                    }
+                    url.set_path(&format!("/{}", segments.join("/")));
+                    request.url = url.to_string();
+                }
+            }
+            _ => log::warn!("Cannot inject into {}: missing location details", self),
+        }
+
+        request
+    }
+}
+
+impl fmt::Display for InjectionPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.location, self.name)
+    }
+}
+
+/// Replaces the first value of `name` in a query string or form body with
+/// already encoded text, appending the pair if it is missing; other pairs keep
+/// their original encoding
+fn replace_pair_value(query: &str, name: &str, encoded: &str) -> String {
+    let mut replaced = false;
+
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let raw_name = pair.split_once('=').map_or(pair, |(n, _)| n);
+            let decoded = percent_decode_str(&raw_name.replace('+', " ")).decode_utf8_lossy().into_owned();
+            if !replaced && decoded == name {
+                replaced = true;
+                format!("{}={}", raw_name, encoded)
+            } else {
+                pair.to_string()
+            }
+        })
+        .collect();
+
+    if !replaced {
+        pairs.push(format!("{}={}", Encoder::Url.encode(name), encoded));
+    }
+    pairs.join("&")
+}
+
+/// Path segments that hold values rather than route names: numbers, UUIDs and hashes
+pub(crate) fn is_value_segment(segment: &str) -> bool {
+    if segment.is_empty() {
+        return false;
+    }
+    let all_digits = segment.chars().all(|c| c.is_ascii_digit());
+    let hex_like = segment.len() >= 8
+        && segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && segment.chars().any(|c| c.is_ascii_digit());
+    all_digits || hex_like
+}
+
+fn collect_json_points(value: &JsonValue, path: &mut Vec<JsonStep>, points: &mut Vec<InjectionPoint>) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, child) in map {
+                path.push(JsonStep::Key(key.clone()));
+                collect_json_points(child, path, points);
+                path.pop();
+            }
+        }
+        JsonValue::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                path.push(JsonStep::Index(index));
+                collect_json_points(child, path, points);
+                path.pop();
+            }
+        }
+        JsonValue::Null => {}
+        scalar => {
+            let original = match scalar {
+                JsonValue::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            points.push(InjectionPoint {
+                location: InjectionLocation::JsonPath,
+                name: json_path_name(path),
+                original,
+                encoding: None,
+                locator: Locator::Json(path.clone()),
+            });
+        }
+    }
+}
+
+fn json_path_name(path: &[JsonStep]) -> String {
+    let mut name = String::from("$");
+    for step in path {
+        match step {
+            JsonStep::Key(key) => {
+                name.push('.');
+                name.push_str(key);
+            }
+            JsonStep::Index(index) => name.push_str(&format!("[{}]", index)),
+        }
+    }
+    name
+}
+
+fn json_pointer_mut<'a>(value: &'a mut JsonValue, path: &[JsonStep]) -> Option<&'a mut JsonValue> {
+    path.iter().try_fold(value, |current, step| match step {
+        JsonStep::Key(key) => current.get_mut(key.as_str()),
+        JsonStep::Index(index) => current.get_mut(*index),
+    })
+}
+
+/// Adds a point for every element whose only content is non-blank text
+fn collect_xml_points(body: &str, points: &mut Vec<InjectionPoint>) {
+    let Ok(document) = roxmltree::Document::parse(body) else {
+        return;
+    };
+
+    for node in document.descendants().filter(|n| n.is_element()) {
+        let mut children = node.children();
+        let (Some(text), None) = (children.next(), children.next()) else {
+            continue;
+        };
+        if !text.is_text() || text.text().is_none_or(|t| t.trim().is_empty()) {
+            continue;
+        }
+
+        let path: Vec<&str> = node
+            .ancestors()
+            .filter(|n| n.is_element())
+            .map(|n| n.tag_name().name())
+            .collect();
+        let name = format!("/{}", path.into_iter().rev().collect::<Vec<_>>().join("/"));
+
+        points.push(InjectionPoint {
+            location: InjectionLocation::XmlNode,
+            name,
+            original: text.text().unwrap_or_default().to_string(),
+            encoding: None,
+            locator: Locator::Xml(text.range()),
+        });
+    }
+}
+
+/// Adds a point for every multipart part that is not a file upload
+fn collect_multipart_points(body: &str, boundary: &str, points: &mut Vec<InjectionPoint>) {
+    let delimiter = format!("--{}", boundary);
+    let starts: Vec<usize> = body.match_indices(&delimiter).map(|(index, _)| index + delimiter.len()).collect();
+
+    for (start, next) in starts.iter().zip(starts.iter().skip(1)) {
+        let part_end = next - delimiter.len();
+        let part = &body[*start..part_end];
+        let Some((headers, separator)) = part.find("\r\n\r\n").map(|i| (&part[..i], 4)).or_else(|| part.find("\n\n").map(|i| (&part[..i], 2)))
+        else {
+            continue;
+        };
+
+        let disposition = headers
+            .lines()
+            .find(|line| line.to_ascii_lowercase().starts_with("content-disposition:"))
+            .unwrap_or("");
+        if disposition.to_ascii_lowercase().contains("filename=") {
+            continue;
+        }
+        let Some(name) = disposition_parameter(disposition, "name") else {
+            continue;
+        };
+
+        let content_start = start + headers.len() + separator;
+        let content = &body[content_start..part_end];
+        let content_len = content.strip_suffix("\r\n").or_else(|| content.strip_suffix('\n')).unwrap_or(content).len();
+        points.push(InjectionPoint {
+            location: InjectionLocation::MultipartField,
+            name,
+            original: body[content_start..content_start + content_len].to_string(),
+            encoding: None,
+            locator: Locator::Multipart(content_start..content_start + content_len),
+        });
+    }
+}
+
+/// A `key="value"` parameter of a `Content-Disposition` header
+fn disposition_parameter(header: &str, key: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.trim().split_once('=')?;
+        name.trim().eq_ignore_ascii_case(key).then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(points: &'a [InjectionPoint], location: InjectionLocation, name: &str) -> &'a InjectionPoint {
+        points
+            .iter()
+            .find(|p| p.location == location && p.name == name)
+            .unwrap_or_else(|| panic!("no {} '{}' in {:?}", location, name, points))
+    }
+
+    #[test]
+    fn test_query_injection_replaces_in_place() {
+        let request = HttpRequest::get("http://example.com/list?cat=a%20b&id=7&id=8#top");
+        let points = InjectionPoint::discover(&request);
+        assert_eq!(points.iter().filter(|p| p.location == InjectionLocation::Query).count(), 2);
+
+        let id = find(&points, InjectionLocation::Query, "id");
+        let injected = id.inject(&request, &format!("{}' AND 1=1-- ", id.original));
+        assert_eq!(injected.url, "http://example.com/list?cat=a%20b&id=7%27%20AND%201%3D1--%20&id=8#top");
+
+        let injected = InjectionPoint::query("sort", "1").inject(&request, "1&x=y");
+        assert_eq!(injected.url, "http://example.com/list?cat=a%20b&id=7&id=8&sort=1%26x%3Dy#top");
+    }
+
+    #[test]
+    fn test_json_body_points() {
+        let mut request = HttpRequest::get("http://example.com/api/users/42");
+        request.method = "POST".to_string();
+        request.set_header("Content-Type", "application/json");
+        request.body = Some(r#"{"user":{"id":7,"tags":["a"]},"q":"shoes","none":null}"#.to_string());
+
+        let points = InjectionPoint::discover(&request);
+        assert_eq!(find(&points, InjectionLocation::PathSegment, "42").original, "42");
+        assert_eq!(find(&points, InjectionLocation::JsonPath, "$.user.id").original, "7");
+        find(&points, InjectionLocation::JsonPath, "$.user.tags[0]");
+
+        let injected = find(&points, InjectionLocation::JsonPath, "$.user.id").inject(&request, "7' AND 1=1--");
+        let json: JsonValue = serde_json::from_str(injected.body.as_deref().unwrap()).unwrap();
+        assert_eq!(json["user"]["id"], "7' AND 1=1--");
+        assert_eq!(json["q"], "shoes");
+
+        let injected = find(&points, InjectionLocation::PathSegment, "42").inject(&request, "42' OR 'a");
+        assert_eq!(injected.url, "http://example.com/api/users/42%27%20OR%20%27a");
+    }
+
+    #[test]
+    fn test_xml_form_cookie_and_header_points() {
+        let mut request = HttpRequest::get("http://example.com/order");
+        request.set_header("Content-Type", "application/xml");
+        request.set_header("Cookie", "session=abc; lang=en");
+        request.set_header("X-Tenant", "acme");
+        request.body = Some("<order><id>5</id><note>a &amp; b</note></order>".to_string());
+
+        let points = InjectionPoint::discover(&request);
+        let injected = find(&points, InjectionLocation::XmlNode, "/order/id").inject(&request, "5'<x>");
+        assert_eq!(injected.body.as_deref(), Some("<order><id>5&apos;&lt;x&gt;</id><note>a &amp; b</note></order>"));
+
+        let injected = find(&points, InjectionLocation::Cookie, "lang").inject(&request, "en' AND 1=1;");
+        assert_eq!(injected.header("cookie"), Some("session=abc; lang=en%27%20AND%201%3D1%3B"));
+
+        let injected = find(&points, InjectionLocation::Header, "X-Tenant").inject(&request, "acme'");
+        assert_eq!(injected.header("x-tenant"), Some("acme'"));
+
+        let mut form = HttpRequest::get("http://example.com/login");
+        form.body = Some("user=bob&pass=x+y".to_string());
+        let points = InjectionPoint::discover(&form);
+        let injected = find(&points, InjectionLocation::FormField, "user").inject(&form, "bob' --");
+        assert_eq!(injected.body.as_deref(), Some("user=bob%27%20--&pass=x+y"));
+    }
+
+    #[test]
+    fn test_multipart_points_and_encoding() {
+        let mut request = HttpRequest::get("http://example.com/upload?id=7");
+        request.method = "POST".to_string();
+        request.set_multipart_body(&[("title".to_string(), "cat".to_string()), ("tags".to_string(), "a b".to_string())]);
+        let body = request.body.clone().unwrap();
+
+        let points = InjectionPoint::discover(&request);
+        let title = find(&points, InjectionLocation::MultipartField, "title");
+        assert_eq!((title.original.as_str(), title.pipeline()), ("cat", EncodingPipeline::raw()));
+
+        let injected = title.inject(&request, "cat'\"");
+        assert_eq!(injected.body, Some(body.replace("\r\n\r\ncat\r\n", "\r\n\r\ncat'\"\r\n")));
+
+        let id = find(&points, InjectionLocation::Query, "id").clone();
+        let base64 = id.with_encoding(EncodingPipeline::raw().then(Encoder::Base64).then(Encoder::Url));
+        assert_eq!(base64.inject(&request, "7'>").url, "http://example.com/upload?id=Nyc%2B");
+    }
+}
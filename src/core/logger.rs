@@ -1,42 +1,300 @@
 // src/core/logger.rs
 
 use chrono::Utc;
-use std::fs::OpenOptions;
-use std::io::Write;
+use log::kv::{Key, Value, VisitSource};
+use log::LevelFilter;
+use serde_json::{Map, Value as JsonValue};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Scan currently being executed; its fields are attached to every log record
+/// and, when per-scan logging is enabled, records are copied to its own file.
+struct ScanContext {
+    scan_id: String,
+    module: String,
+    target: String,
+    file: Option<File>,
+}
+
+static SCAN_CONTEXT: Mutex<Option<ScanContext>> = Mutex::new(None);
+static SCAN_LOG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Output format of log records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable `[LEVEL] timestamp - message key=value` lines
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Where log records are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogDestination {
+    Stderr,
+    File,
+    Both,
+}
+
+/// Logging configuration, usually built from command line flags
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    /// Explicit level; when `None` the `RUST_LOG` environment variable is used, falling back to `info`
+    pub level: Option<LevelFilter>,
+    pub format: LogFormat,
+    pub destination: LogDestination,
+    /// Main log file used by the `File` and `Both` destinations
+    pub file: PathBuf,
+    /// Directory receiving one `<scan_id>.log` file per scan, if set
+    pub scan_log_dir: Option<PathBuf>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: None,
+            format: LogFormat::Text,
+            destination: LogDestination::File,
+            file: PathBuf::from("redlines.log"),
+            scan_log_dir: None,
+        }
+    }
+}
 
 /// Logger helper for initialization and structured scan health logs.
 pub struct Logger;
 
 impl Logger {
-    /// Initialize logging to file and stdout with timestamps.
+    /// Initialize logging to `redlines.log` with the default configuration.
     pub fn init() {
-        let log_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("redlines.log")
-            .unwrap();
-
-        env_logger::Builder::new()
-            .format(|buf, record| {
-                let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
-                writeln!(buf, "[{}] {} - {}", record.level(), timestamp, record.args())
+        Self::init_with(&LogConfig::default()).expect("failed to initialize logging");
+    }
+
+    /// Initialize logging with the given level, format and destinations.
+    pub fn init_with(config: &LogConfig) -> io::Result<()> {
+        let file = match config.destination {
+            LogDestination::File | LogDestination::Both => Some(
+                OpenOptions::new().create(true).append(true).open(&config.file)?,
+            ),
+            LogDestination::Stderr => None,
+        };
+        let sink = LogSink {
+            stderr: matches!(config.destination, LogDestination::Stderr | LogDestination::Both),
+            file,
+        };
+
+        if let Some(dir) = &config.scan_log_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+        *SCAN_LOG_DIR.lock().unwrap() = config.scan_log_dir.clone();
+
+        let mut builder = env_logger::Builder::new();
+        match (config.level, std::env::var("RUST_LOG")) {
+            (Some(level), _) => builder.filter_level(level),
+            (None, Ok(filters)) => builder.parse_filters(&filters),
+            (None, Err(_)) => builder.filter_level(LevelFilter::Info),
+        };
+
+        let format = config.format;
+        builder
+            .format(move |buf, record| {
+                let line = match format {
+                    LogFormat::Text => format_text(record),
+                    LogFormat::Json => format_json(record),
+                };
+                writeln!(buf, "{}", line)
             })
-            .target(env_logger::Target::Pipe(Box::new(log_file)))
-            .init();
+            .target(env_logger::Target::Pipe(Box::new(sink)))
+            .try_init()
+            .map_err(io::Error::other)
     }
 
-    /// Log start of scan.
-    pub fn scan_start(target: &str) {
-        log::info!("🚀 Starting scan: {}", target);
+    /// Log general informational message.
+    pub fn info(message: &str) {
+        log::info!("{}", message);
     }
 
-    /// Log completion of scan with summary.
+    /// Log start of scan and attach its id, module and target to subsequent records.
+    pub fn scan_start(scan_id: &str, module: &str, target: &str) {
+        let file = SCAN_LOG_DIR.lock().unwrap().as_deref().and_then(|dir| open_scan_log(dir, scan_id));
+        *SCAN_CONTEXT.lock().unwrap() = Some(ScanContext {
+            scan_id: scan_id.to_string(),
+            module: module.to_string(),
+            target: target.to_string(),
+            file,
+        });
+
+        log::info!(event = "scan_start"; "🚀 Starting scan: {}", target);
+    }
+
+    /// Log completion of scan with summary and detach the scan context.
     pub fn scan_complete(target: &str, vuln_count: usize, duration: std::time::Duration) {
-        log::info!("✅ Scan completed: {} - Found {} vulnerabilities in {:?}", target, vuln_count, duration);
+        log::info!(
+            event = "scan_complete",
+            vulnerabilities = vuln_count,
+            duration_ms = duration.as_millis() as u64;
+            "✅ Scan completed: {} - Found {} vulnerabilities in {:?}", target, vuln_count, duration
+        );
+
+        *SCAN_CONTEXT.lock().unwrap() = None;
+    }
+
+    /// Log a scan or crawl that ended with an error and detach the scan context.
+    pub fn scan_failed(target: &str, error: &str, duration: std::time::Duration) {
+        log::error!(
+            event = "scan_failed",
+            error = error,
+            duration_ms = duration.as_millis() as u64;
+            "❌ Scan failed: {} after {:?}: {}", target, duration, error
+        );
+
+        *SCAN_CONTEXT.lock().unwrap() = None;
+    }
+
+    /// Returns the id and module of the scan currently running, if any.
+    pub fn current_scan() -> Option<(String, String)> {
+        SCAN_CONTEXT
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|ctx| (ctx.scan_id.clone(), ctx.module.clone()))
     }
 
     /// Log single vulnerability discovered.
     pub fn vulnerability_found(vuln_type: &str, location: &str) {
-        log::warn!("⚠️ Vulnerability found: {} at {}", vuln_type, location);
+        log::warn!(
+            event = "vulnerability_found",
+            vuln_type = vuln_type,
+            location = location;
+            "⚠️ Vulnerability found: {} at {}", vuln_type, location
+        );
+    }
+}
+
+fn open_scan_log(dir: &Path, scan_id: &str) -> Option<File> {
+    let path = dir.join(format!("{}.log", scan_id));
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open scan log {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Formats a record as `[LEVEL] timestamp - message key=value ...`
+fn format_text(record: &log::Record) -> String {
+    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
+    let mut line = format!("[{}] {} - {}", record.level(), timestamp, record.args());
+
+    let mut fields = collect_fields(record);
+    if let Some(ctx) = SCAN_CONTEXT.lock().unwrap().as_ref() {
+        fields.insert("scan_id".to_string(), JsonValue::String(ctx.scan_id.clone()));
+        fields.insert("module".to_string(), JsonValue::String(ctx.module.clone()));
+        fields.insert("target".to_string(), JsonValue::String(ctx.target.clone()));
+    }
+    for (key, value) in fields {
+        match value {
+            JsonValue::String(s) => line.push_str(&format!(" {}={:?}", key, s)),
+            other => line.push_str(&format!(" {}={}", key, other)),
+        }
+    }
+    line
+}
+
+/// Formats a record as a single-line JSON object including the scan context
+fn format_json(record: &log::Record) -> String {
+    let mut object = Map::new();
+    object.insert("timestamp".to_string(), JsonValue::String(Utc::now().to_rfc3339()));
+    object.insert("level".to_string(), JsonValue::String(record.level().to_string()));
+    object.insert("logger".to_string(), JsonValue::String(record.target().to_string()));
+    object.insert("message".to_string(), JsonValue::String(record.args().to_string()));
+
+    if let Some(ctx) = SCAN_CONTEXT.lock().unwrap().as_ref() {
+        object.insert("scan_id".to_string(), JsonValue::String(ctx.scan_id.clone()));
+        object.insert("module".to_string(), JsonValue::String(ctx.module.clone()));
+        object.insert("target".to_string(), JsonValue::String(ctx.target.clone()));
+    }
+    object.extend(collect_fields(record));
+
+    JsonValue::Object(object).to_string()
+}
+
+/// Collects the structured key-value pairs attached to a record
+fn collect_fields(record: &log::Record) -> Map<String, JsonValue> {
+    struct Collector(Map<String, JsonValue>);
+
+    impl<'kvs> VisitSource<'kvs> for Collector {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+            let json = if let Some(n) = value.to_u64() {
+                JsonValue::from(n)
+            } else if let Some(n) = value.to_i64() {
+                JsonValue::from(n)
+            } else if let Some(b) = value.to_bool() {
+                JsonValue::from(b)
+            } else {
+                JsonValue::String(value.to_string())
+            };
+            self.0.insert(key.to_string(), json);
+            Ok(())
+        }
+    }
+
+    let mut collector = Collector(Map::new());
+    let _ = record.key_values().visit(&mut collector);
+    collector.0
+}
+
+/// Writes formatted records to the configured destinations and the active scan log
+struct LogSink {
+    stderr: bool,
+    file: Option<File>,
+}
+
+impl Write for LogSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.stderr {
+            io::stderr().write_all(buf)?;
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(buf)?;
+        }
+        if let Some(file) = SCAN_CONTEXT.lock().unwrap().as_mut().and_then(|ctx| ctx.file.as_mut()) {
+            file.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_format_includes_structured_fields() {
+        let fields: &[(&str, &str)] = &[("event", "vulnerability_found"), ("location", "id")];
+        let line = format_json(
+            &log::Record::builder()
+                .args(format_args!("found"))
+                .level(log::Level::Warn)
+                .target("redlines_x::test")
+                .key_values(&fields)
+                .build(),
+        );
+        let json: JsonValue = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["message"], "found");
+        assert_eq!(json["event"], "vulnerability_found");
+        assert_eq!(json["location"], "id");
     }
 }
@@ -7,13 +7,28 @@ pub mod scanner;
 pub mod request_engine;
 pub mod scan_result;
 pub mod vulnerability;
+pub mod audit;
+pub mod scope;
+pub mod safety;
+pub mod http_request;
+pub mod injection;
+pub mod encoding;
+pub mod site_map;
 
 // Re-exports for easier access
 pub use session::Session;
 pub use database::Database;
 pub use modules::SecurityModule;
 pub use module_handler::ModuleHandler;  // ← وتأكد من إضافة هذا السطر
+pub use logger::{LogConfig, LogDestination, LogFormat, Logger};
 pub use scanner::ScannerEngine;
 pub use request_engine::RequestEngine;
-pub use scan_result::ScanResult;
-pub use vulnerability::{Vulnerability, SeverityLevel};
\ No newline at end of file
+pub use scan_result::{ScanResult, ScanRecord};
+pub use vulnerability::{Vulnerability, SeverityLevel};
+pub use audit::{AuditEntry, AuditLog};
+pub use scope::{Scope, ScopeError};
+pub use safety::{RiskLevel, Safety, SafetyError};
+pub use http_request::{BodyKind, HttpRequest};
+pub use injection::{InjectionLocation, InjectionPoint};
+pub use encoding::{Encoder, EncodingPipeline};
+pub use site_map::{Endpoint, EndpointSource, SiteMap};
@@ -1,4 +1,6 @@
+use crate::core::http_request::HttpRequest;
 use crate::core::modules::SecurityModule;
+use crate::core::site_map::Endpoint;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -57,6 +59,52 @@ impl ModuleHandler {
         }
     }
     
+    /// Executes a specific module against a full request
+    pub async fn run_module_request(
+        &self,
+        module_name: &str,
+        request: &HttpRequest,
+    ) -> Result<crate::core::scan_result::ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        match self.get_module(module_name) {
+            Some(module) => {
+                log::info!("Executing module '{}' on request: {} {}", module_name, request.method, request.url);
+                module.run_request(request).await
+            }
+            None => Err(format!("Module '{}' not found", module_name).into()),
+        }
+    }
+    
+    /// Executes a module against several endpoints, such as those of a site
+    /// map, and merges the results; a failed endpoint becomes a warning
+    pub async fn run_module_endpoints(
+        &self,
+        module_name: &str,
+        endpoints: &[Endpoint],
+    ) -> Result<crate::core::scan_result::ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        let module = self
+            .get_module(module_name)
+            .ok_or_else(|| format!("Module '{}' not found", module_name))?;
+        log::info!("Executing module '{}' on {} endpoints", module_name, endpoints.len());
+
+        let mut merged = crate::core::scan_result::ScanResult {
+            success: false,
+            vulnerabilities: Vec::new(),
+            warnings: Vec::new(),
+            duration: std::time::Duration::ZERO,
+        };
+        for endpoint in endpoints {
+            let request = &endpoint.request;
+            match module.run_endpoint(endpoint).await {
+                Ok(result) => merged.merge(result),
+                Err(e) => {
+                    log::warn!("Module '{}' failed on {} {}: {}", module_name, request.method, request.url, e);
+                    merged.warnings.push(format!("Failed to scan {} {}: {}", request.method, request.url, e));
+                }
+            }
+        }
+        Ok(merged)
+    }
+    
     /// Returns detailed information about all modules
     pub fn get_module_info(&self) -> Vec<ModuleInfo> {
         self.modules
@@ -1,6 +1,6 @@
+use crate::core::http_request::HttpRequest;
+use crate::core::site_map::Endpoint;
 use async_trait::async_trait;
-use crate::core::scan_result::ScanResult;
-use crate::core::vulnerability::{Vulnerability, SeverityLevel};
 
 #[async_trait]
 pub trait SecurityModule {
@@ -10,6 +10,21 @@ pub trait SecurityModule {
     fn version(&self) -> &str;
     
     async fn run(&self, target: &str) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Runs the module against a full request (method, headers, cookies and body)
+    ///
+    /// Modules that only understand URLs keep the default, which scans `request.url`.
+    async fn run_request(&self, request: &HttpRequest) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.run(&request.url).await
+    }
+
+    /// Runs the module against an endpoint of a site map or imported API
+    ///
+    /// The default scans the endpoint's request; modules that inject payloads
+    /// should also test `endpoint.injection_points`.
+    async fn run_endpoint(&self, endpoint: &Endpoint) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_request(&endpoint.request).await
+    }
 }
 
 // Re-exports
@@ -1,8 +1,20 @@
+use crate::core::audit::{AuditEntry, AuditLog};
+use crate::core::http_request::HttpRequest;
+use crate::core::logger::Logger;
+use crate::core::safety::{RiskLevel, Safety, SafetyError};
+use crate::core::scope::Scope;
+use chrono::Utc;
 use reqwest::{Client, Response};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use std::collections::HashMap;
 
+/// Redirects followed for one request before giving up
+const MAX_REDIRECTS: usize = 10;
+
+/// Headers dropped when a redirect leaves the origin they were sent to
+const ORIGIN_BOUND_HEADERS: [&str; 3] = ["authorization", "cookie", "proxy-authorization"];
+
 pub struct RequestEngine {
     client: Client,
     rate_limiter: Mutex<()>,
@@ -15,6 +27,8 @@ impl RequestEngine {
             .timeout(Duration::from_secs(30))
             .user_agent("Redlines-X-Scanner/1.0")
             .danger_accept_invalid_certs(true)
+            // Redirects are followed by `send_request_at_risk` so each hop is checked and audited
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .unwrap();
 
@@ -25,31 +39,127 @@ impl RequestEngine {
         }
     }
 
+    /// Sends a request without a payload and records it in the audit trail
+    ///
+    /// `purpose` describes why the request is sent (e.g. "sqli-error-probe") and is
+    /// stored with the scan id and module of the running scan. Requests outside the
+    /// active scope fail with a `ScopeError` without being sent, as do methods that
+    /// may change data when the policy does not allow it (see `HttpRequest::risk`).
     pub async fn send_request(
         &self,
         url: &str,
         method: &str,
         headers: Option<HashMap<String, String>>,
         body: Option<String>,
+        purpose: &str,
+    ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+        let request = HttpRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: headers.unwrap_or_default(),
+            body,
+        };
+        self.send(&request, purpose, RiskLevel::ReadOnly).await
+    }
+
+    /// Sends a request carrying a payload of the given risk level
+    ///
+    /// Besides the scope check, the request must pass the safety policy: it fails
+    /// with a `SafetyError` without being sent if its risk level is not enabled or,
+    /// in safe mode, if its URL, headers, cookies or body contain state-changing SQL.
+    /// Redirects are followed hop by hop, each one checked and audited like the
+    /// first request; a hop that fails the checks is not followed and its redirect
+    /// response is returned instead.
+    pub async fn send_request_at_risk(
+        &self,
+        url: &str,
+        method: &str,
+        headers: Option<HashMap<String, String>>,
+        body: Option<String>,
+        purpose: &str,
+        risk: RiskLevel,
+    ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+        if let Err(e) = Scope::check_active(url) {
+            log::warn!(purpose = purpose; "Blocked request: {}", e);
+            return Err(Box::new(e));
+        }
+        if let Err(e) = Safety::check(url, headers.as_ref(), body.as_deref(), risk) {
+            log::warn!(purpose = purpose; "Blocked request: {}", e);
+            return Err(Box::new(e));
+        }
+
+        let (mut method, mut headers, mut body) = (method.to_uppercase(), headers, body);
+        let mut response = self.send_once(url, &method, headers.clone(), body.clone(), purpose, None).await?;
+        for _ in 0..MAX_REDIRECTS {
+            let Some(next) = redirect_target(&response) else {
+                return Ok(response);
+            };
+            if let Err(e) = Scope::check_active(next.as_str()) {
+                log::warn!(purpose = purpose; "Not following redirect: {}", e);
+                return Ok(response);
+            }
+
+            // 303, and 301/302 after a POST, continue as a GET without the body
+            let status = response.status().as_u16();
+            if (status == 303 && method != "HEAD") || (matches!(status, 301 | 302) && method == "POST") {
+                method = "GET".to_string();
+                body = None;
+                if let Some(headers) = headers.as_mut() {
+                    headers.retain(|name, _| !name.to_ascii_lowercase().starts_with("content-"));
+                }
+            }
+            if next.origin() != response.url().origin()
+                && let Some(headers) = headers.as_mut()
+            {
+                headers.retain(|name, _| !ORIGIN_BOUND_HEADERS.contains(&name.to_ascii_lowercase().as_str()));
+            }
+            if let Err(e) = Safety::check(next.as_str(), headers.as_ref(), body.as_deref(), risk) {
+                log::warn!(purpose = purpose; "Not following redirect: {}", e);
+                return Ok(response);
+            }
+
+            let from = response.url().to_string();
+            response = self.send_once(next.as_str(), &method, headers.clone(), body.clone(), purpose, Some(from)).await?;
+        }
+
+        if redirect_target(&response).is_some() {
+            return Err(format!("too many redirects from {}", url).into());
+        }
+        Ok(response)
+    }
+
+    /// Sends a single request that already passed the checks and audits it;
+    /// `redirected_from` is the URL whose redirect led here
+    async fn send_once(
+        &self,
+        url: &str,
+        method: &str,
+        headers: Option<HashMap<String, String>>,
+        body: Option<String>,
+        purpose: &str,
+        redirected_from: Option<String>,
     ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
         let _guard = self.rate_limiter.lock().await;
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let mut request = match method.to_uppercase().as_str() {
+        let mut request = match method {
             "GET" => self.client.get(url),
             "POST" => self.client.post(url),
             "PUT" => self.client.put(url),
             "DELETE" => self.client.delete(url),
+            "PATCH" => self.client.patch(url),
+            "HEAD" => self.client.head(url),
             _ => return Err("Unsupported HTTP method".into()),
         };
 
+        let recorded_headers = headers.as_ref().map(AuditEntry::recorded_headers).unwrap_or_default();
         if let Some(headers_map) = headers {
             for (key, value) in headers_map {
                 request = request.header(&key, &value);
             }
         }
 
-        if let Some(body_content) = body {
+        if let Some(body_content) = body.clone() {
             request = request.body(body_content);
         }
 
@@ -57,11 +167,80 @@ impl RequestEngine {
         *count += 1;
         log::debug!("Sending request #{} to {}", count, url);
 
-        let response = request.send().await?;
-        Ok(response)
+        let timestamp = Utc::now();
+        let started = Instant::now();
+        let result = request.send().await;
+        let duration = started.elapsed();
+
+        let (scan_id, module) = Logger::current_scan().unzip();
+        let entry = AuditEntry {
+            timestamp,
+            scan_id,
+            module,
+            purpose: purpose.to_string(),
+            method: method.to_string(),
+            url: url.to_string(),
+            redirected_from,
+            headers: recorded_headers,
+            body,
+            status: result.as_ref().ok().map(|r| r.status().as_u16()),
+            duration_ms: duration.as_millis() as u64,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        log::debug!(
+            purpose = purpose,
+            method = entry.method.as_str(),
+            url = url,
+            status = entry.status.unwrap_or(0),
+            duration_ms = entry.duration_ms;
+            "Request #{} completed", count
+        );
+        AuditLog::record(&entry);
+
+        Ok(result?)
+    }
+
+    /// Sends a prepared `HttpRequest` with the given payload risk level
+    ///
+    /// Replaying a form submission or any other request that may change data
+    /// by its method needs unsafe methods to be enabled (see
+    /// `Safety::allows_request`); the payload risk is checked as usual.
+    pub async fn send(
+        &self,
+        request: &HttpRequest,
+        purpose: &str,
+        risk: RiskLevel,
+    ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+        if !Safety::allows_request(request.risk()) {
+            let e = SafetyError {
+                url: request.url.clone(),
+                reason: format!("{} requests may change data and are not enabled", request.method),
+            };
+            log::warn!(purpose = purpose; "Blocked request: {}", e);
+            return Err(Box::new(e));
+        }
+        let headers = (!request.headers.is_empty()).then(|| request.headers.clone());
+        self.send_request_at_risk(&request.url, &request.method, headers, request.body.clone(), purpose, risk)
+            .await
     }
 
     pub async fn get_request_count(&self) -> u32 {
         *self.request_count.lock().await
     }
-}
\ No newline at end of file
+}
+
+/// The absolute URL a redirect response points to, if it is one
+fn redirect_target(response: &Response) -> Option<reqwest::Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(reqwest::header::LOCATION)?.to_str().ok()?;
+    response.url().join(location).ok()
+}
+
+/// Default implementation for RequestEngine
+impl Default for RequestEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
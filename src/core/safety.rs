@@ -0,0 +1,234 @@
+// src/core/safety.rs
+
+use crate::core::encoding::Encoder;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, RwLock};
+
+static SAFE_MODE: AtomicBool = AtomicBool::new(true);
+static UNSAFE_METHODS: AtomicBool = AtomicBool::new(false);
+static MAX_RISK: RwLock<RiskLevel> = RwLock::new(RiskLevel::ReadOnly);
+
+/// Statements that modify data or schema, matched on the decoded request
+static STATE_CHANGING_SQL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)
+        \b(?:drop|create|alter|truncate|rename)\s+(?:table|database|schema|index|view|user|procedure|function|trigger)\b
+        | \binsert\s+into\b
+        | \bdelete\s+from\b
+        | \bupdate\s+[\w.\[\]`]+\s+set\b
+        | \bmerge\s+into\b
+        | \b(?:grant|revoke)\s+\w+
+        | \bxp_cmdshell\b
+        | \bshutdown\b
+        | \binto\s+(?:out|dump)file\b",
+    )
+    .expect("state-changing SQL pattern is valid")
+});
+
+/// How much a payload may affect the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RiskLevel {
+    /// Only reads data; safe against production systems
+    ReadOnly,
+    /// Read-only but expensive, e.g. time delays that tie up database connections
+    Heavy,
+    /// May modify data or schema, e.g. `OR 1=1` reaching an UPDATE or DELETE
+    StateChanging,
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::ReadOnly => "read-only",
+            RiskLevel::Heavy => "heavy",
+            RiskLevel::StateChanging => "state-changing",
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "read-only" | "readonly" => Ok(RiskLevel::ReadOnly),
+            "heavy" => Ok(RiskLevel::Heavy),
+            "state-changing" => Ok(RiskLevel::StateChanging),
+            other => Err(format!(
+                "unknown risk level '{}' (expected read-only, heavy or state-changing)",
+                other
+            )),
+        }
+    }
+}
+
+/// Error returned when a request is blocked by the safety policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyError {
+    pub url: String,
+    pub reason: String,
+}
+
+impl fmt::Display for SafetyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Blocked by safety policy: {} ({})", self.url, self.reason)
+    }
+}
+
+impl std::error::Error for SafetyError {}
+
+/// Process-wide payload safety policy enforced by `RequestEngine`.
+///
+/// Safe mode is on by default and blocks every state-changing payload, whatever
+/// risk level the scan allows. Independently, each scan sets the highest risk
+/// level it opts into (read-only by default). Whether requests that may change
+/// data by their method alone, such as form submissions, may be replayed is a
+/// separate opt-in that leaves safe mode's SQL check in place.
+pub struct Safety;
+
+impl Safety {
+    /// Enables or disables safe mode
+    pub fn set_safe_mode(enabled: bool) {
+        SAFE_MODE.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn safe_mode() -> bool {
+        SAFE_MODE.load(Ordering::SeqCst)
+    }
+
+    /// Sets the highest risk level the current scan opts into
+    pub fn set_max_risk(risk: RiskLevel) {
+        *MAX_RISK.write().unwrap() = risk;
+    }
+
+    /// Highest risk level that may currently be sent, taking safe mode into account
+    pub fn max_risk() -> RiskLevel {
+        let max = *MAX_RISK.read().unwrap();
+        if Self::safe_mode() {
+            max.min(RiskLevel::Heavy)
+        } else {
+            max
+        }
+    }
+
+    /// Returns true if payloads of the given risk level may be sent
+    pub fn allows(risk: RiskLevel) -> bool {
+        risk <= Self::max_risk()
+    }
+
+    /// Allows replaying POST, PUT, PATCH, DELETE and other requests that may
+    /// change data whatever is injected into them
+    pub fn set_unsafe_methods(enabled: bool) {
+        UNSAFE_METHODS.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn unsafe_methods() -> bool {
+        UNSAFE_METHODS.load(Ordering::SeqCst)
+    }
+
+    /// Returns true if a request of the given own risk (see `HttpRequest::risk`)
+    /// may be sent
+    pub fn allows_request(risk: RiskLevel) -> bool {
+        risk < RiskLevel::StateChanging || Self::unsafe_methods()
+    }
+
+    /// Checks a request before it is sent
+    ///
+    /// Rejects requests whose declared risk exceeds the policy and, in safe mode,
+    /// any request whose decoded URL, header values (cookies included) or body
+    /// contains a data- or schema-modifying SQL statement regardless of its
+    /// declared risk.
+    pub fn check(
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        body: Option<&str>,
+        risk: RiskLevel,
+    ) -> Result<(), SafetyError> {
+        let blocked = |reason: String| SafetyError { url: url.to_string(), reason };
+
+        if !Self::allows(risk) {
+            return Err(blocked(format!(
+                "{} payloads are not enabled (maximum is {})",
+                risk,
+                Self::max_risk()
+            )));
+        }
+
+        if Self::safe_mode() {
+            let header_values = headers.into_iter().flat_map(|headers| headers.values().map(String::as_str));
+            for text in [Some(url), body].into_iter().flatten().chain(header_values) {
+                if let Some(found) = find_state_changing_sql(text) {
+                    return Err(blocked(format!("safe mode forbids state-changing SQL '{}'", found)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Words long enough to be base64-encoded statements
+static BASE64_WORD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9+/]{16,}={0,2}").expect("base64 pattern is valid"));
+
+/// Finds a state-changing SQL statement in possibly percent-encoded text, also
+/// looking through the encodings payloads can be sent with
+fn find_state_changing_sql(text: &str) -> Option<String> {
+    let decoded = percent_decode_str(&text.replace('+', " ")).decode_utf8_lossy().into_owned();
+    let mut layers = vec![
+        Encoder::Url.decode(&decoded),
+        Encoder::Html.decode(&decoded),
+        Encoder::Unicode.decode(&decoded),
+    ];
+    layers.extend(BASE64_WORD.find_iter(&decoded).map(|word| Encoder::Base64.decode(word.as_str())));
+
+    std::iter::once(&decoded)
+        .chain(&layers)
+        .find_map(|layer| STATE_CHANGING_SQL.find(layer).map(|m| m.as_str().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_state_changing_sql() {
+        assert!(find_state_changing_sql("http://x/?id=1%27%3B%20DROP%20TABLE%20users--").is_some());
+        assert!(find_state_changing_sql("http://x/?id=1';+delete+from+orders").is_some());
+        assert!(find_state_changing_sql("{\"q\":\"1; UPDATE users SET role='admin'\"}").is_some());
+        assert!(find_state_changing_sql("http://x/?id=1'; EXEC xp_cmdshell 'dir'--").is_some());
+        // Hidden by an encoding layer
+        assert!(find_state_changing_sql("http://x/?id=1%2527%253B%2520DROP%2520TABLE%2520users").is_some());
+        assert!(find_state_changing_sql("{\"q\":\"1\\u0027; DELETE FROM orders\"}").is_some());
+        assert!(find_state_changing_sql("http://x/?id=MSc7IERST1AgVEFCTEUgdXNlcnMtLQ%3D%3D").is_some());
+
+        assert!(find_state_changing_sql("http://x/?id=1' AND UPDATEXML(1,CONCAT(0x5c,VERSION()),1)--").is_none());
+        assert!(find_state_changing_sql("http://x/api/delete?id=3").is_none());
+        assert!(find_state_changing_sql("http://x/?q=created+table+tennis").is_none());
+    }
+
+    #[test]
+    fn test_check_blocks_header_and_cookie_payloads() {
+        let url = "http://x/";
+        let header = HashMap::from([("X-Forwarded-For".to_string(), "1'; DROP TABLE users--".to_string())]);
+        let cookie = HashMap::from([("Cookie".to_string(), "a=b; id=1%27%3B%20DELETE%20FROM%20orders".to_string())]);
+        let benign = HashMap::from([("Cookie".to_string(), "id=1' AND 1=1--".to_string())]);
+
+        assert!(Safety::check(url, Some(&header), None, RiskLevel::ReadOnly).is_err());
+        assert!(Safety::check(url, Some(&cookie), None, RiskLevel::ReadOnly).is_err());
+        assert!(Safety::check(url, Some(&benign), None, RiskLevel::ReadOnly).is_ok());
+    }
+}
@@ -1,8 +1,11 @@
 // src/core/scan_result.rs
 
-use crate::core::vulnerability::Vulnerability;
+use crate::core::scope::Scope;
+use crate::core::vulnerability::{SeverityLevel, Vulnerability};
+use chrono::{DateTime, Utc};
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScanResult {
@@ -10,4 +13,70 @@ pub struct ScanResult {
     pub vulnerabilities: Vec<Vulnerability>,
     pub warnings: Vec<String>,
     pub duration: Duration,
+}
+
+impl ScanResult {
+    /// Counts findings per severity level, ordered from most to least severe
+    pub fn severity_counts(&self) -> Vec<(SeverityLevel, usize)> {
+        SeverityLevel::ALL
+            .iter()
+            .map(|level| {
+                let count = self.vulnerabilities.iter().filter(|v| v.severity == *level).count();
+                (*level, count)
+            })
+            .collect()
+    }
+
+    /// Adds another run's findings, warnings and duration to this result;
+    /// findings with the same title and request as an existing one are dropped
+    pub fn merge(&mut self, other: ScanResult) {
+        self.success |= other.success;
+        for vulnerability in other.vulnerabilities {
+            let duplicate = self
+                .vulnerabilities
+                .iter()
+                .any(|v| v.title == vulnerability.title && v.request == vulnerability.request);
+            if !duplicate {
+                self.vulnerabilities.push(vulnerability);
+            }
+        }
+        for warning in other.warnings {
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+        self.duration += other.duration;
+    }
+
+    /// Returns true if any finding is at or above the given severity
+    pub fn has_findings_at_or_above(&self, threshold: SeverityLevel) -> bool {
+        self.vulnerabilities.iter().any(|v| v.severity >= threshold)
+    }
+}
+
+/// A scan result stored in the database along with what was scanned and when
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanRecord {
+    pub id: String,
+    pub target: String,
+    pub module: String,
+    pub started_at: DateTime<Utc>,
+    /// Scope enforced while the scan ran
+    #[serde(default)]
+    pub scope: Option<Scope>,
+    pub result: ScanResult,
+}
+
+impl ScanRecord {
+    /// Wraps a finished scan result into a new record with a fresh id
+    pub fn new(target: &str, module: &str, started_at: DateTime<Utc>, result: ScanResult) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            target: target.to_string(),
+            module: module.to_string(),
+            started_at,
+            scope: None,
+            result,
+        }
+    }
 }
\ No newline at end of file
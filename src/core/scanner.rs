@@ -74,4 +74,11 @@ impl ScannerEngine {
         }
         Err("All scan attempts failed".into())
     }
-}
\ No newline at end of file
+}
+
+/// Default implementation for ScannerEngine
+impl Default for ScannerEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
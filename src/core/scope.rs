@@ -0,0 +1,219 @@
+// src/core/scope.rs
+
+use ipnet::IpNet;
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::RwLock;
+
+static ACTIVE_SCOPE: RwLock<Option<Scope>> = RwLock::new(None);
+
+/// Defines which hosts and paths a scan may send requests to.
+///
+/// A URL is in scope when its host matches `hosts` (exact names or
+/// `*.example.com` wildcards, which match subdomains only) or falls in one of
+/// the `cidrs`, its path starts with one of `path_prefixes` (any path if
+/// empty) and it does not start with any of `excluded_paths`. Paths are
+/// percent-decoded and normalized before matching; exclusions ignore case when
+/// `case_insensitive_exclusions` is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scope {
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub cidrs: Vec<IpNet>,
+    #[serde(default)]
+    pub path_prefixes: Vec<String>,
+    #[serde(default)]
+    pub excluded_paths: Vec<String>,
+    #[serde(default)]
+    pub case_insensitive_exclusions: bool,
+}
+
+/// Error returned when a request falls outside the active scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeError {
+    InvalidUrl { url: String },
+    OutOfScope { url: String, reason: String },
+}
+
+impl fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScopeError::InvalidUrl { url } => write!(f, "Invalid URL: {}", url),
+            ScopeError::OutOfScope { url, reason } => write!(f, "Out of scope: {} ({})", url, reason),
+        }
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+impl Scope {
+    /// Creates a scope limited to the host of the given target URL
+    pub fn for_target(target: &str) -> Result<Self, ScopeError> {
+        let url = Url::parse(target).map_err(|_| ScopeError::InvalidUrl { url: target.to_string() })?;
+        let host = url.host_str().ok_or_else(|| ScopeError::InvalidUrl { url: target.to_string() })?;
+
+        Ok(Self {
+            hosts: vec![host.trim_start_matches('[').trim_end_matches(']').to_lowercase()],
+            ..Self::default()
+        })
+    }
+
+    /// Loads a scope definition from a JSON file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Checks whether the URL may be requested under this scope
+    pub fn check(&self, url: &str) -> Result<(), ScopeError> {
+        let parsed = Url::parse(url).map_err(|_| ScopeError::InvalidUrl { url: url.to_string() })?;
+        let out_of_scope = |reason: String| ScopeError::OutOfScope { url: url.to_string(), reason };
+
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(out_of_scope(format!("scheme '{}' is not allowed", parsed.scheme())));
+        }
+
+        let host = parsed.host_str().ok_or_else(|| out_of_scope("URL has no host".to_string()))?;
+        if !self.host_allowed(host) {
+            return Err(out_of_scope(format!("host '{}' is not allowed", host)));
+        }
+
+        let path = normalize_path(parsed.path());
+        let excluded = self.excluded_paths.iter().find(|excluded| {
+            if self.case_insensitive_exclusions {
+                path.to_lowercase().starts_with(&excluded.to_lowercase())
+            } else {
+                path.starts_with(excluded.as_str())
+            }
+        });
+        if let Some(excluded) = excluded {
+            return Err(out_of_scope(format!("path matches exclusion '{}'", excluded)));
+        }
+        if !self.path_prefixes.is_empty() && !self.path_prefixes.iter().any(|p| path.starts_with(p.as_str())) {
+            return Err(out_of_scope(format!("path '{}' is outside the allowed prefixes", path)));
+        }
+
+        Ok(())
+    }
+
+    fn host_allowed(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+
+        if let Ok(ip) = host.parse::<IpAddr>()
+            && self.cidrs.iter().any(|net| net.contains(&ip))
+        {
+            return true;
+        }
+
+        self.hosts.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            match allowed.strip_prefix("*.") {
+                Some(domain) => host.ends_with(&format!(".{}", domain)),
+                None => host == allowed,
+            }
+        })
+    }
+
+    /// Makes this scope the one enforced for every request in this process
+    pub fn activate(scope: Scope) {
+        *ACTIVE_SCOPE.write().unwrap() = Some(scope);
+    }
+
+    /// Removes the active scope; requests are no longer restricted
+    pub fn deactivate() {
+        *ACTIVE_SCOPE.write().unwrap() = None;
+    }
+
+    /// Checks the URL against the active scope, if one is set
+    pub fn check_active(url: &str) -> Result<(), ScopeError> {
+        match ACTIVE_SCOPE.read().unwrap().as_ref() {
+            Some(scope) => scope.check(url),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Percent-decodes a URL path and resolves empty, `.` and `..` segments, so
+/// encoded or roundabout spellings of a path match the same rules
+fn normalize_path(path: &str) -> String {
+    let decoded = percent_decode_str(path).decode_utf8_lossy();
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut normalized = format!("/{}", segments.join("/"));
+    if decoded.ends_with('/') && !segments.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope() -> Scope {
+        Scope {
+            hosts: vec!["app.example.com".to_string(), "*.api.example.com".to_string()],
+            cidrs: vec!["10.0.0.0/24".parse().unwrap()],
+            path_prefixes: Vec::new(),
+            excluded_paths: vec!["/logout".to_string()],
+            case_insensitive_exclusions: false,
+        }
+    }
+
+    #[test]
+    fn test_hosts_wildcards_and_cidrs() {
+        let scope = scope();
+        assert!(scope.check("https://app.example.com/login").is_ok());
+        assert!(scope.check("https://APP.example.com/").is_ok());
+        assert!(scope.check("https://v1.api.example.com/users").is_ok());
+        assert!(scope.check("https://api.example.com/").is_err());
+        assert!(scope.check("https://evil.com/?next=app.example.com").is_err());
+        assert!(scope.check("http://10.0.0.42:8080/").is_ok());
+        assert!(scope.check("http://10.0.1.1/").is_err());
+        assert!(scope.check("ftp://app.example.com/").is_err());
+    }
+
+    #[test]
+    fn test_path_rules() {
+        let mut scope = scope();
+        assert!(matches!(
+            scope.check("https://app.example.com/logout?all=1"),
+            Err(ScopeError::OutOfScope { .. })
+        ));
+
+        // Encoded and dot-segment spellings do not slip past an exclusion
+        assert!(scope.check("https://app.example.com/%6Cogout").is_err());
+        assert!(scope.check("https://app.example.com/x/%2E%2E/logout").is_err());
+        assert!(scope.check("https://app.example.com/static%2F..%2Flogout").is_err());
+        assert!(scope.check("https://app.example.com//logout").is_err());
+        assert!(scope.check("https://app.example.com/LogOut").is_ok());
+        scope.case_insensitive_exclusions = true;
+        assert!(scope.check("https://app.example.com/LogOut").is_err());
+
+        scope.path_prefixes = vec!["/api/".to_string()];
+        assert!(scope.check("https://app.example.com/api/items").is_ok());
+        assert!(scope.check("https://app.example.com/admin").is_err());
+    }
+
+    #[test]
+    fn test_for_target() {
+        let scope = Scope::for_target("http://Example.com:8080/shop?id=1").unwrap();
+        assert_eq!(scope.hosts, vec!["example.com"]);
+        assert!(scope.check("http://example.com/other").is_ok());
+        assert!(scope.check("http://cdn.example.com/").is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::core::encoding::URL_COMPONENT;
+use crate::core::http_request::HttpRequest;
+use percent_encoding::utf8_percent_encode;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use chrono::{Utc, DateTime};
@@ -32,4 +35,56 @@ impl Session {
     pub fn get_cookie(&self, name: &str) -> Option<&String> {
         self.cookies.get(name)
     }
+
+    /// Header value by name, ignoring case
+    pub fn get_header(&self, name: &str) -> Option<&String> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value)
+    }
+
+    /// Adds the session's headers and cookies to a request; headers replace
+    /// existing ones, cookies are added to those the request already sends.
+    /// `{{name}}` placeholders in the URL are filled with session variables,
+    /// percent-encoded so they are safe in a path segment or a query value.
+    pub fn apply(&self, request: &mut HttpRequest) {
+        for (name, value) in &self.variables {
+            let placeholder = format!("{{{{{}}}}}", name);
+            if request.url.contains(&placeholder) {
+                let value = utf8_percent_encode(value, URL_COMPONENT).to_string();
+                request.url = request.url.replace(&placeholder, &value);
+            }
+        }
+
+        let mut names: Vec<&String> = self.headers.keys().collect();
+        names.sort();
+        for name in names {
+            request.set_header(name, &self.headers[name]);
+        }
+
+        if self.cookies.is_empty() {
+            return;
+        }
+        let mut cookies = request.cookies();
+        let mut names: Vec<&String> = self.cookies.keys().collect();
+        names.sort();
+        for name in names {
+            cookies.retain(|(existing, _)| existing != name);
+            cookies.push((name.clone(), self.cookies[name].clone()));
+        }
+        let header: Vec<String> = cookies.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        request.set_header("Cookie", &header.join("; "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_percent_encodes_variables() {
+        let mut session = Session::new("http://example.com");
+        session.variables.insert("user".to_string(), "a b/c+d".to_string());
+        let mut request = HttpRequest::get("http://example.com/users/{{user}}?q={{user}}");
+        session.apply(&mut request);
+        assert_eq!(request.url, "http://example.com/users/a%20b%2Fc%2Bd?q=a%20b%2Fc%2Bd");
+    }
 }
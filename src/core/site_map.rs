@@ -0,0 +1,201 @@
+// src/core/site_map.rs
+
+use crate::core::http_request::HttpRequest;
+use crate::core::injection::{is_value_segment, InjectionLocation, InjectionPoint};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use url::Url;
+use uuid::Uuid;
+
+/// Resources that never contain links or parameters worth testing
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "css", "js", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "bmp", "woff", "woff2", "ttf",
+    "eot", "otf", "mp3", "mp4", "webm", "avi", "pdf", "zip", "gz", "tar", "rar", "7z", "exe", "dmg", "iso",
+];
+
+/// How an endpoint was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndpointSource {
+    /// The URL the crawl started from
+    Target,
+    Link,
+    Form,
+    Robots,
+    Sitemap,
+    /// An operation of an OpenAPI document
+    OpenApi,
+    /// A request recorded in a HAR file
+    Har,
+    /// A request of a Postman collection
+    Postman,
+    /// An operation generated from a GraphQL schema
+    #[serde(rename = "graphql")]
+    GraphQl,
+}
+
+/// A request the application answers, with the parameters it takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Endpoint {
+    /// Example request, including parameter values seen in the application
+    pub request: HttpRequest,
+    /// Names of the declared injection points and of the request's query and
+    /// body parameters
+    pub parameters: Vec<String>,
+    /// Points that cannot be found from the request alone, such as named path
+    /// parameters; modules test them in addition to the discovered ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub injection_points: Vec<InjectionPoint>,
+    pub source: EndpointSource,
+    /// Links followed from the start URL to reach the endpoint
+    pub depth: usize,
+    /// Status code of the response, if the endpoint was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+impl Endpoint {
+    pub fn new(request: HttpRequest, source: EndpointSource, depth: usize) -> Self {
+        let parameters = parameter_names(&request);
+        Self { request, parameters, injection_points: Vec::new(), source, depth, status: None, content_type: None }
+    }
+}
+
+/// Endpoints of one application, as found by a crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteMap {
+    pub id: String,
+    /// URL the site map was built from
+    pub target: String,
+    pub created_at: DateTime<Utc>,
+    pub endpoints: Vec<Endpoint>,
+    /// Stored session applied to the requests when they are sent, so the site
+    /// map itself holds no credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// `endpoint_key` of every endpoint; rebuilt on demand after loading
+    #[serde(skip)]
+    keys: HashSet<String>,
+}
+
+impl SiteMap {
+    pub fn new(target: &str) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            target: target.to_string(),
+            created_at: Utc::now(),
+            endpoints: Vec::new(),
+            session: None,
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Adds an endpoint unless a near-identical one is already present; returns
+    /// whether it was added
+    pub fn add(&mut self, endpoint: Endpoint) -> bool {
+        if self.keys.is_empty() && !self.endpoints.is_empty() {
+            self.keys = self.endpoints.iter().map(|e| endpoint_key(&e.request)).collect();
+        }
+        if !self.keys.insert(endpoint_key(&endpoint.request)) {
+            return false;
+        }
+        self.endpoints.push(endpoint);
+        true
+    }
+
+    /// Returns true if a near-identical request is already in the site map
+    pub fn contains(&self, request: &HttpRequest) -> bool {
+        let key = endpoint_key(request);
+        if self.keys.is_empty() {
+            return self.endpoints.iter().any(|e| endpoint_key(&e.request) == key);
+        }
+        self.keys.contains(&key)
+    }
+
+    /// Requests of every endpoint, in the order they were found
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.endpoints.iter().map(|e| e.request.clone()).collect()
+    }
+}
+
+/// Identifies near-identical requests: same method, origin and route (with
+/// id-like path segments ignored) and the same parameter names
+pub fn endpoint_key(request: &HttpRequest) -> String {
+    let route = match Url::parse(&request.url) {
+        Ok(url) => {
+            let segments: Vec<&str> = url
+                .path()
+                .split('/')
+                .map(|segment| if is_value_segment(segment) { "{id}" } else { segment })
+                .collect();
+            format!("{}{}", url.origin().ascii_serialization(), segments.join("/"))
+        }
+        Err(_) => request.url.clone(),
+    };
+    let mut names = parameter_names(request);
+    names.sort();
+    names.dedup();
+    // GraphQL operations share one URL and are told apart by name
+    if request.is_graphql()
+        && let Some(operation) = request
+            .body
+            .as_deref()
+            .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok())
+            .and_then(|json| json.get("operationName").and_then(|name| name.as_str()).map(str::to_string))
+    {
+        return format!("{} {} {} {}", request.method, route, operation, names.join("&"));
+    }
+    format!("{} {} {}", request.method, route, names.join("&"))
+}
+
+/// Names of the query and body parameters of a request
+fn parameter_names(request: &HttpRequest) -> Vec<String> {
+    InjectionPoint::discover(request)
+        .into_iter()
+        .filter(|p| {
+            matches!(
+                p.location,
+                InjectionLocation::Query
+                    | InjectionLocation::FormField
+                    | InjectionLocation::JsonPath
+                    | InjectionLocation::XmlNode
+                    | InjectionLocation::MultipartField
+            )
+        })
+        .map(|p| p.name)
+        .collect()
+}
+
+/// Returns true for static files (stylesheets, scripts, images, fonts, media, archives)
+pub fn is_skipped_resource(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return true;
+    };
+    url.path()
+        .rsplit_once('.')
+        .is_some_and(|(_, extension)| SKIPPED_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_identical_endpoints() {
+        let mut site_map = SiteMap::new("http://example.com/");
+        assert!(site_map.add(Endpoint::new(HttpRequest::get("http://example.com/item/7?tab=info"), EndpointSource::Link, 1)));
+        assert!(!site_map.add(Endpoint::new(HttpRequest::get("http://example.com/item/8?tab=reviews"), EndpointSource::Link, 2)));
+        assert!(site_map.add(Endpoint::new(HttpRequest::get("http://example.com/item/8?tab=reviews&page=2"), EndpointSource::Link, 2)));
+        assert!(site_map.add(Endpoint::new(HttpRequest::get("http://example.com/items"), EndpointSource::Link, 1)));
+
+        assert_eq!(site_map.endpoints.len(), 3);
+        assert_eq!(site_map.endpoints[0].parameters, vec!["tab"]);
+        assert!(site_map.contains(&HttpRequest::get("http://example.com/item/123?tab=x")));
+
+        assert!(is_skipped_resource("https://example.com/static/app.JS"));
+        assert!(!is_skipped_resource("https://example.com/post.php?id=1"));
+    }
+}
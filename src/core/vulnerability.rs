@@ -1,5 +1,7 @@
 /// Defines a vulnerability detection result
 use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Defines a vulnerability detection result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,13 +10,72 @@ pub struct Vulnerability {
     pub description: String,
     pub severity: SeverityLevel,
     pub evidence: String,
+    /// Raw request that triggered the finding, if captured
+    #[serde(default)]
+    pub request: Option<String>,
+    /// Relevant excerpt of the response, if captured
+    #[serde(default)]
+    pub response: Option<String>,
+    /// Recommended fix for the finding
+    #[serde(default)]
+    pub remediation: String,
+    /// Backend database identified for injection findings, if fingerprinted
+    #[serde(default)]
+    pub dbms: Option<String>,
+    /// Where the injected input is reflected in the response, e.g. `script
+    /// block` or `not reflected`, if probed
+    #[serde(default)]
+    pub reflection: Option<String>,
 }
 
 /// Severity levels for vulnerabilities
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum SeverityLevel {
     Low,
     Medium,
     High,
     Critical,
+}
+
+impl SeverityLevel {
+    /// All severity levels, from most to least severe
+    pub const ALL: [SeverityLevel; 4] = [
+        SeverityLevel::Critical,
+        SeverityLevel::High,
+        SeverityLevel::Medium,
+        SeverityLevel::Low,
+    ];
+
+    /// Returns the human readable name of the severity level
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeverityLevel::Low => "Low",
+            SeverityLevel::Medium => "Medium",
+            SeverityLevel::High => "High",
+            SeverityLevel::Critical => "Critical",
+        }
+    }
+}
+
+impl fmt::Display for SeverityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SeverityLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(SeverityLevel::Low),
+            "medium" => Ok(SeverityLevel::Medium),
+            "high" => Ok(SeverityLevel::High),
+            "critical" => Ok(SeverityLevel::Critical),
+            other => Err(format!(
+                "unknown severity '{}' (expected low, medium, high or critical)",
+                other
+            )),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,293 @@
+// src/import/har.rs
+
+// HAR (HTTP Archive) import
+
+use crate::core::http_request::HttpRequest;
+use crate::core::session::Session;
+use crate::core::site_map::{is_skipped_resource, Endpoint, EndpointSource, SiteMap};
+use crate::import::{merge_session, take_credentials};
+use serde::Deserialize;
+use std::path::Path;
+use url::Url;
+
+/// Headers that describe the recorded connection rather than the request, or
+/// that would turn replays into `304 Not Modified`
+const DROPPED_HEADERS: &[&str] = &[
+    "host", "content-length", "connection", "keep-alive", "proxy-connection", "transfer-encoding", "te", "upgrade",
+    "accept-encoding", "if-none-match", "if-modified-since",
+];
+
+/// Methods that never reach application code
+const SKIPPED_METHODS: &[&str] = &["OPTIONS", "CONNECT", "TRACE"];
+
+/// Response types of static resources
+const STATIC_CONTENT_TYPES: &[&str] = &["image/", "font/", "audio/", "video/", "text/css", "javascript"];
+
+#[derive(Debug, Deserialize)]
+struct HarDocument {
+    log: HarLog,
+}
+
+#[derive(Debug, Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HarEntry {
+    request: HarRequest,
+    #[serde(default)]
+    response: Option<HarResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarPair>,
+    #[serde(default)]
+    cookies: Vec<HarPair>,
+    #[serde(default)]
+    post_data: Option<HarPostData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarPostData {
+    #[serde(default)]
+    mime_type: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<HarParam>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarParam {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    file_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HarResponse {
+    #[serde(default)]
+    status: u16,
+    #[serde(default)]
+    cookies: Vec<HarPair>,
+    #[serde(default)]
+    content: Option<HarContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarContent {
+    #[serde(default)]
+    mime_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct HarPair {
+    name: String,
+    #[serde(default)]
+    value: String,
+}
+
+/// Browser traffic recorded as a HAR file
+pub struct HarArchive {
+    entries: Vec<HarEntry>,
+}
+
+impl HarArchive {
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let document: HarDocument = serde_json::from_str(source).map_err(|e| format!("not a HAR document: {}", e))?;
+        Ok(Self { entries: document.log.entries })
+    }
+
+    /// Loads a HAR file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| format!("Invalid HAR file {}: {}", path.display(), e).into())
+    }
+
+    /// Origin of the first application request, normally the page the
+    /// recording started on
+    pub fn origin(&self) -> Option<String> {
+        self.entries.iter().filter(|entry| is_application_request(entry)).find_map(|entry| {
+            let url = Url::parse(&entry.request.url).ok()?;
+            Some(url.origin().ascii_serialization())
+        })
+    }
+
+    /// Turns the recorded requests to `origin` into endpoints. Cookies and
+    /// credential headers seen in the recording are moved to `session`, which
+    /// keeps its own values; the latest recorded value of each wins otherwise.
+    /// The site map refers to the session, which is applied when requests are sent.
+    pub fn to_site_map(
+        &self,
+        origin: &str,
+        session: &mut Session,
+    ) -> Result<SiteMap, Box<dyn std::error::Error + Send + Sync>> {
+        let origin = Url::parse(origin)?.origin();
+        let entries: Vec<&HarEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| is_application_request(entry))
+            .filter(|entry| Url::parse(&entry.request.url).is_ok_and(|url| url.origin() == origin))
+            .collect();
+
+        let mut recorded = Session::new(&origin.ascii_serialization());
+        let mut site_map = SiteMap::new(&origin.ascii_serialization());
+        for entry in entries {
+            let mut request = replay_request(&entry.request);
+            take_credentials(&mut request, &mut recorded);
+            for cookie in entry.response.iter().flat_map(|response| &response.cookies) {
+                recorded.add_cookie(&cookie.name, &cookie.value);
+            }
+
+            let mut endpoint = Endpoint::new(request, EndpointSource::Har, 0);
+            if let Some(response) = &entry.response {
+                endpoint.status = Some(response.status).filter(|status| *status > 0);
+                endpoint.content_type = response
+                    .content
+                    .as_ref()
+                    .map(|content| content.mime_type.clone())
+                    .filter(|mime_type| !mime_type.is_empty());
+            }
+            site_map.add(endpoint);
+        }
+        merge_session(session, &recorded);
+        site_map.session = Some(session.id.clone());
+
+        log::info!(
+            "Imported {} endpoints from {} recorded requests to {}",
+            site_map.endpoints.len(),
+            self.entries.len(),
+            site_map.target
+        );
+        Ok(site_map)
+    }
+}
+
+/// Returns false for static resources, preflights and non-HTTP requests
+fn is_application_request(entry: &HarEntry) -> bool {
+    let request = &entry.request;
+    if !request.url.starts_with("http://") && !request.url.starts_with("https://") {
+        return false;
+    }
+    if SKIPPED_METHODS.contains(&request.method.to_ascii_uppercase().as_str()) || is_skipped_resource(&request.url) {
+        return false;
+    }
+    let mime_type = entry
+        .response
+        .as_ref()
+        .and_then(|response| response.content.as_ref())
+        .map(|content| content.mime_type.to_ascii_lowercase())
+        .unwrap_or_default();
+    !STATIC_CONTENT_TYPES.iter().any(|static_type| mime_type.contains(static_type))
+}
+
+/// The recorded request without connection-specific headers
+fn replay_request(recorded: &HarRequest) -> HttpRequest {
+    let mut request = HttpRequest::get(&recorded.url);
+    request.method = recorded.method.to_ascii_uppercase();
+    for header in &recorded.headers {
+        let name = header.name.to_ascii_lowercase();
+        if name.starts_with(':') || DROPPED_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        // HTTP/2 recordings may split cookies over several headers
+        match request.header("Cookie") {
+            Some(existing) if name == "cookie" => {
+                let joined = format!("{}; {}", existing, header.value);
+                request.set_header("Cookie", &joined);
+            }
+            _ => request.set_header(&header.name, &header.value),
+        }
+    }
+    if request.header("Cookie").is_none() && !recorded.cookies.is_empty() {
+        let pairs: Vec<String> = recorded.cookies.iter().map(|c| format!("{}={}", c.name, c.value)).collect();
+        request.set_header("Cookie", &pairs.join("; "));
+    }
+
+    if let Some(post_data) = &recorded.post_data {
+        let body = match &post_data.text {
+            Some(text) if !text.is_empty() => Some(text.clone()),
+            _ if !post_data.params.is_empty() => {
+                let fields = post_data.params.iter().filter(|p| p.file_name.is_none()).map(|p| (&p.name, &p.value));
+                Some(url::form_urlencoded::Serializer::new(String::new()).extend_pairs(fields).finish())
+            }
+            _ => None,
+        };
+        if body.is_some() && request.header("Content-Type").is_none() && !post_data.mime_type.is_empty() {
+            request.set_header("Content-Type", &post_data.mime_type);
+        }
+        request.body = body;
+    }
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::safety::RiskLevel;
+
+    const HAR: &str = r#"{"log": {"version": "1.2", "entries": [
+        {"request": {"method": "GET", "url": "https://shop.example.com/search?q=lamp",
+                     "headers": [{"name": ":authority", "value": "shop.example.com"},
+                                 {"name": "Accept-Encoding", "value": "gzip, br"},
+                                 {"name": "Cookie", "value": "sid=old; theme=dark"}],
+                     "cookies": [{"name": "sid", "value": "old"}, {"name": "theme", "value": "dark"}]},
+         "response": {"status": 200, "cookies": [{"name": "sid", "value": "new"}],
+                      "content": {"mimeType": "text/html; charset=utf-8"}}},
+        {"request": {"method": "GET", "url": "https://shop.example.com/static/app.js", "headers": []},
+         "response": {"status": 200, "content": {"mimeType": "application/javascript"}}},
+        {"request": {"method": "GET", "url": "https://cdn.analytics.example/collect?id=1", "headers": []}},
+        {"request": {"method": "OPTIONS", "url": "https://shop.example.com/api/cart", "headers": []}},
+        {"request": {"method": "POST", "url": "https://shop.example.com/api/cart",
+                     "headers": [{"name": "Authorization", "value": "Bearer abc"},
+                                 {"name": "Content-Length", "value": "24"}],
+                     "postData": {"mimeType": "application/json", "text": "{\"item\":7,\"quantity\":1}"}},
+         "response": {"status": 201, "content": {"mimeType": "application/json"}}}
+    ]}}"#;
+
+    #[test]
+    fn test_har_endpoints_and_session() {
+        let archive = HarArchive::parse(HAR).unwrap();
+        assert_eq!(archive.origin().as_deref(), Some("https://shop.example.com"));
+
+        let mut session = Session::new("https://shop.example.com");
+        session.add_cookie("theme", "light");
+        let site_map = archive.to_site_map("https://shop.example.com", &mut session).unwrap();
+        assert_eq!(site_map.endpoints.len(), 2);
+        assert_eq!(site_map.session.as_deref(), Some(session.id.as_str()));
+
+        assert_eq!(session.get_cookie("sid").map(String::as_str), Some("new"));
+        assert_eq!(session.get_cookie("theme").map(String::as_str), Some("light"));
+        assert_eq!(session.get_header("authorization").map(String::as_str), Some("Bearer abc"));
+
+        let search = &site_map.endpoints[0];
+        assert_eq!(search.request.url, "https://shop.example.com/search?q=lamp");
+        assert_eq!(search.request.header("Cookie"), None);
+        let mut sent = search.request.clone();
+        session.apply(&mut sent);
+        assert_eq!(sent.header("Cookie"), Some("sid=new; theme=light"));
+        assert_eq!(search.request.header("Accept-Encoding"), None);
+        assert_eq!(search.request.header(":authority"), None);
+        assert_eq!(search.status, Some(200));
+
+        let cart = &site_map.endpoints[1];
+        assert_eq!(cart.request.method, "POST");
+        assert_eq!(cart.request.risk(), RiskLevel::StateChanging);
+        assert_eq!(cart.request.header("Content-Type"), Some("application/json"));
+        assert_eq!(cart.request.header("Content-Length"), None);
+        assert_eq!(cart.request.header("Authorization"), None);
+        assert_eq!(cart.parameters, vec!["$.item", "$.quantity"]);
+    }
+}
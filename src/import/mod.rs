@@ -0,0 +1,78 @@
+// src/import/mod.rs
+
+// Imports of scan targets from API descriptions and recorded traffic
+
+pub mod har;
+pub mod openapi;
+pub mod postman;
+
+pub use har::HarArchive;
+pub use openapi::OpenApiSpec;
+pub use postman::PostmanCollection;
+
+use crate::core::http_request::HttpRequest;
+use crate::core::session::Session;
+use serde_json::Value as JsonValue;
+
+/// Parts of header names that mark them as carrying credentials
+const CREDENTIAL_HEADER_HINTS: &[&str] = &["authorization", "x-auth", "token", "api-key", "apikey", "csrf", "xsrf", "session"];
+
+/// Returns true for headers that authenticate a request, such as
+/// `Authorization`, `X-API-Key` or `X-CSRF-Token`
+pub(crate) fn is_credential_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    !name.starts_with(':') && CREDENTIAL_HEADER_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Adds the headers, cookies and variables found in imported traffic to a
+/// session, keeping the values the session already has
+pub(crate) fn merge_session(session: &mut Session, found: &Session) {
+    for (name, value) in &found.headers {
+        if session.get_header(name).is_none() {
+            session.headers.insert(name.clone(), value.clone());
+        }
+    }
+    for (name, value) in &found.cookies {
+        session.cookies.entry(name.clone()).or_insert_with(|| value.clone());
+    }
+    for (name, value) in &found.variables {
+        session.variables.entry(name.clone()).or_insert_with(|| value.clone());
+    }
+}
+
+/// Moves the cookies and credential headers of an imported request into
+/// `found`, replacing values it already has, so stored site maps carry no
+/// credentials; the session is applied again when the request is sent
+pub(crate) fn take_credentials(request: &mut HttpRequest, found: &mut Session) {
+    for (name, value) in request.cookies() {
+        found.add_cookie(&name, &value);
+    }
+    let names: Vec<String> =
+        request.headers.keys().filter(|name| name.eq_ignore_ascii_case("cookie") || is_credential_header(name)).cloned().collect();
+    for name in names {
+        let value = request.headers.remove(&name).unwrap_or_default();
+        if !name.eq_ignore_ascii_case("cookie") {
+            found.headers.retain(|key, _| !key.eq_ignore_ascii_case(&name));
+            found.headers.insert(name, value);
+        }
+    }
+}
+
+/// Adds a `{{variable}}` placeholder for a query credential; `Session::apply`
+/// fills it in from the session's variables when the request is sent
+pub(crate) fn add_query_credential(request: &mut HttpRequest, name: &str, variable: &str) {
+    let separator = if request.url.contains('?') { '&' } else { '?' };
+    let name: String = url::form_urlencoded::byte_serialize(name.as_bytes()).collect();
+    request.url = format!("{}{}{}={{{{{}}}}}", request.url, separator, name, variable);
+}
+
+/// A value as it appears in a URL, header or form field; arrays give their
+/// first item
+pub(crate) fn scalar_string(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Null => String::new(),
+        JsonValue::Array(items) => items.first().map(scalar_string).unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
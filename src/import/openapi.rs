@@ -0,0 +1,599 @@
+// src/import/openapi.rs
+
+// OpenAPI 3 import
+
+use crate::core::http_request::HttpRequest;
+use crate::core::injection::InjectionPoint;
+use crate::core::session::Session;
+use crate::core::site_map::{Endpoint, EndpointSource, SiteMap};
+use crate::import::{add_query_credential, merge_session, scalar_string};
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::{Map, Value as JsonValue};
+use std::path::Path;
+use url::Url;
+
+/// HTTP methods an OpenAPI path item can describe, in the order they are imported
+const METHODS: &[&str] = &["get", "post", "put", "patch", "delete", "head"];
+
+/// Nesting depth at which schema examples stop, so recursive schemas terminate
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+/// Links followed in a chain of `$ref`s before giving up
+const MAX_REF_HOPS: usize = 16;
+
+/// Headers set by the scanner itself, never taken from parameter definitions
+const RESERVED_HEADERS: &[&str] = &["accept", "content-type", "authorization"];
+
+/// Session variables tried, in order, for bearer and OAuth tokens
+const TOKEN_VARIABLES: &[&str] = &["token", "bearer_token", "access_token"];
+
+/// An OpenAPI 3 document, kept as JSON so `$ref`s can be resolved anywhere
+pub struct OpenApiSpec {
+    document: JsonValue,
+}
+
+impl OpenApiSpec {
+    /// Parses a JSON or YAML document; only OpenAPI 3.x is supported
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let document: JsonValue = match serde_json::from_str(source) {
+            Ok(document) => document,
+            Err(_) => serde_yaml::from_str(source).map_err(|e| format!("not valid JSON or YAML: {}", e))?,
+        };
+
+        match document.get("openapi").and_then(JsonValue::as_str) {
+            Some(version) if version.starts_with('3') => Ok(Self { document }),
+            Some(version) => Err(format!("unsupported OpenAPI version {}", version).into()),
+            None if document.get("swagger").is_some() => Err("Swagger 2.0 documents are not supported; convert to OpenAPI 3".into()),
+            None => Err("missing `openapi` version field".into()),
+        }
+    }
+
+    /// Loads a spec file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| format!("Invalid OpenAPI document {}: {}", path.display(), e).into())
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.document.pointer("/info/title").and_then(JsonValue::as_str)
+    }
+
+    /// URL of the first server, with server variables set to their defaults
+    pub fn server_url(&self) -> Option<String> {
+        let server = self.document.get("servers")?.as_array()?.first()?;
+        let mut url = server.get("url")?.as_str()?.to_string();
+        if let Some(variables) = server.get("variables").and_then(JsonValue::as_object) {
+            for (name, variable) in variables {
+                let default = variable.get("default").map(scalar_string).unwrap_or_default();
+                url = url.replace(&format!("{{{}}}", name), &default);
+            }
+        }
+        Some(url)
+    }
+
+    /// Expands every operation into an endpoint with example values, using
+    /// `base_url` (or the spec's server, resolved against it) as the API root
+    /// and `session` for authentication. Credentials the security schemes need
+    /// are added to `session`, which the site map refers to, rather than to
+    /// the stored requests.
+    pub fn to_site_map(
+        &self,
+        base_url: Option<&str>,
+        session: &mut Session,
+    ) -> Result<SiteMap, Box<dyn std::error::Error + Send + Sync>> {
+        let base = match (base_url, self.server_url()) {
+            (Some(base), Some(server)) if !server.contains("://") => Url::parse(base)?.join(&server)?.to_string(),
+            (Some(base), _) => base.to_string(),
+            (None, Some(server)) if server.contains("://") => server,
+            (None, _) => return Err("the spec has no absolute server URL; pass the API base URL".into()),
+        };
+        Url::parse(&base)?;
+
+        let mut site_map = SiteMap::new(&base);
+        let mut found = Session::new(&base);
+        let paths = self.document.get("paths").and_then(JsonValue::as_object).cloned().unwrap_or_default();
+        for (path, item) in &paths {
+            let item = self.resolve(item);
+            for method in METHODS {
+                if let Some(operation) = item.get(*method) {
+                    let mut endpoint = self.endpoint(&base, path, method, item, operation)?;
+                    self.apply_security(&mut endpoint.request, operation, session, &mut found);
+                    if !site_map.add(endpoint) {
+                        log::debug!("Skipping duplicate operation {} {}", method.to_uppercase(), path);
+                    }
+                }
+            }
+        }
+        merge_session(session, &found);
+        site_map.session = Some(session.id.clone());
+
+        log::info!(
+            "Imported {} endpoints from OpenAPI document{}",
+            site_map.endpoints.len(),
+            self.title().map(|title| format!(" '{}'", title)).unwrap_or_default()
+        );
+        Ok(site_map)
+    }
+
+    fn endpoint(
+        &self,
+        base: &str,
+        path: &str,
+        method: &str,
+        item: &JsonValue,
+        operation: &JsonValue,
+    ) -> Result<Endpoint, Box<dyn std::error::Error + Send + Sync>> {
+        let base_segments = Url::parse(base)?.path_segments().map(|s| s.filter(|s| !s.is_empty()).count()).unwrap_or(0);
+        let template_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut filled_path = path.to_string();
+        let mut path_points = Vec::new();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        let mut cookies = Vec::new();
+
+        for parameter in self.parameters(item, operation) {
+            let Some(name) = parameter.get("name").and_then(JsonValue::as_str) else {
+                continue;
+            };
+            let value = scalar_string(&self.parameter_example(parameter));
+            match parameter.get("in").and_then(JsonValue::as_str) {
+                Some("path") => {
+                    let placeholder = format!("{{{}}}", name);
+                    filled_path = filled_path.replace(&placeholder, &utf8_percent_encode(&value, NON_ALPHANUMERIC).to_string());
+                    // Only whole segments can be rewritten; `/files/{name}.json` keeps its suffix untested
+                    if let Some(index) = template_segments.iter().position(|s| *s == placeholder) {
+                        path_points.push(InjectionPoint::path_segment(base_segments + index, name, &value));
+                    }
+                }
+                Some("query") => query.push((name.to_string(), value)),
+                Some("header") if !RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) => {
+                    headers.push((name.to_string(), value))
+                }
+                Some("cookie") => cookies.push((name.to_string(), value)),
+                _ => {}
+            }
+        }
+
+        let mut url = Url::parse(&format!("{}/{}", base.trim_end_matches('/'), filled_path.trim_start_matches('/')))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&query);
+        }
+
+        let mut request = HttpRequest::get(url.as_str());
+        request.method = method.to_uppercase();
+        for (name, value) in &headers {
+            request.set_header(name, value);
+        }
+        if !cookies.is_empty() {
+            let header: Vec<String> = cookies.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+            request.set_header("Cookie", &header.join("; "));
+        }
+        if let Some(body) = operation.get("requestBody") {
+            self.set_body(&mut request, self.resolve(body));
+        }
+
+        let mut endpoint = Endpoint::new(request, EndpointSource::OpenApi, 0);
+        endpoint.parameters.splice(0..0, path_points.iter().map(|p| p.name.clone()));
+        endpoint.injection_points = path_points;
+        Ok(endpoint)
+    }
+
+    /// Path-level parameters overridden by operation-level ones with the same name and location
+    fn parameters<'a>(&'a self, item: &'a JsonValue, operation: &'a JsonValue) -> Vec<&'a JsonValue> {
+        let mut parameters: Vec<&JsonValue> = Vec::new();
+        for list in [item.get("parameters"), operation.get("parameters")].into_iter().flatten() {
+            for parameter in list.as_array().into_iter().flatten().map(|p| self.resolve(p)) {
+                let key = |p: &JsonValue| (p.get("name").cloned(), p.get("in").cloned());
+                parameters.retain(|existing| key(existing) != key(parameter));
+                parameters.push(parameter);
+            }
+        }
+        parameters
+    }
+
+    fn parameter_example(&self, parameter: &JsonValue) -> JsonValue {
+        if let Some(example) = parameter.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = first_named_example(parameter.get("examples"), |e| self.resolve(e)) {
+            return example;
+        }
+        match parameter.get("schema") {
+            Some(schema) => self.schema_example(schema, 0),
+            None => JsonValue::String("test".to_string()),
+        }
+    }
+
+    /// Sets the body from the preferred media type of a request body definition
+    fn set_body(&self, request: &mut HttpRequest, body: &JsonValue) {
+        let Some(content) = body.get("content").and_then(JsonValue::as_object) else {
+            return;
+        };
+        let preference = |media: &str| match media {
+            m if m == "application/json" || m.ends_with("+json") => 0,
+            "application/x-www-form-urlencoded" => 1,
+            "multipart/form-data" => 2,
+            m if m.ends_with("/xml") || m.ends_with("+xml") => 3,
+            m if m.starts_with("text/") => 4,
+            _ => 5,
+        };
+        let Some((media, definition)) = content.iter().min_by_key(|(media, _)| preference(media)) else {
+            return;
+        };
+
+        let example = definition
+            .get("example")
+            .cloned()
+            .or_else(|| first_named_example(definition.get("examples"), |e| self.resolve(e)))
+            .or_else(|| definition.get("schema").map(|schema| self.schema_example(schema, 0)))
+            .unwrap_or(JsonValue::Null);
+
+        let fields = || -> Vec<(String, String)> {
+            match &example {
+                JsonValue::Object(map) => map.iter().map(|(k, v)| (k.clone(), scalar_string(v))).collect(),
+                _ => Vec::new(),
+            }
+        };
+        let (content_type, body) = match preference(media) {
+            0 => (media.as_str(), example.to_string()),
+            1 => {
+                let fields = fields();
+                let encoded = url::form_urlencoded::Serializer::new(String::new()).extend_pairs(&fields).finish();
+                ("application/x-www-form-urlencoded", encoded)
+            }
+            2 => {
+                request.set_multipart_body(&fields());
+                return;
+            }
+            3 => {
+                let root = definition
+                    .get("schema")
+                    .map(|schema| self.resolve(schema))
+                    .and_then(|schema| schema.pointer("/xml/name"))
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or("root");
+                (media.as_str(), to_xml(root, &example))
+            }
+            _ => (media.as_str(), scalar_string(&example)),
+        };
+        request.set_header("Content-Type", content_type);
+        request.body = Some(body);
+    }
+
+    /// Adds the credentials of the first security requirement the session can
+    /// satisfy to `found`; the operation's requirements override the document's
+    fn apply_security(&self, request: &mut HttpRequest, operation: &JsonValue, session: &Session, found: &mut Session) {
+        let requirements = operation
+            .get("security")
+            .or_else(|| self.document.get("security"))
+            .and_then(JsonValue::as_array)
+            .cloned()
+            .unwrap_or_default();
+        if requirements.is_empty() || requirements.iter().any(|r| r.as_object().is_some_and(Map::is_empty)) {
+            return;
+        }
+
+        for requirement in &requirements {
+            let Some(schemes) = requirement.as_object() else {
+                continue;
+            };
+            let mut candidate = request.clone();
+            let mut credentials = found.clone();
+            if schemes.keys().all(|name| self.apply_scheme(&mut candidate, &mut credentials, name, session)) {
+                *request = candidate;
+                *found = credentials;
+                return;
+            }
+        }
+        log::warn!(
+            "No credentials in the session for {} {}; it is imported without authentication",
+            request.method,
+            request.url
+        );
+    }
+
+    /// Adds the credentials of one security scheme to `found`, or a placeholder
+    /// for a query key to the request; returns false if the session lacks them
+    fn apply_scheme(&self, request: &mut HttpRequest, found: &mut Session, scheme_name: &str, session: &Session) -> bool {
+        let Some(scheme) = self.document.pointer(&format!("/components/securitySchemes/{}", escape_pointer(scheme_name))) else {
+            return false;
+        };
+        let scheme = self.resolve(scheme);
+        let variable = |names: &[&str]| names.iter().find_map(|name| session.variables.get(*name)).cloned();
+        let bearer = || {
+            variable(&[scheme_name])
+                .or_else(|| variable(TOKEN_VARIABLES))
+                .map(|token| format!("Bearer {}", token))
+                .or_else(|| session.get_header("Authorization").cloned())
+        };
+
+        match scheme.get("type").and_then(JsonValue::as_str) {
+            Some("apiKey") => {
+                let name = scheme.get("name").and_then(JsonValue::as_str).unwrap_or(scheme_name);
+                match scheme.get("in").and_then(JsonValue::as_str) {
+                    Some("header") => {
+                        match session.get_header(name).cloned().or_else(|| variable(&[name, scheme_name])) {
+                            Some(value) => set_header(found, name, &value),
+                            None => return false,
+                        }
+                    }
+                    Some("query") => {
+                        let Some(variable) = [name, scheme_name].into_iter().find(|v| session.variables.contains_key(*v)) else {
+                            return false;
+                        };
+                        add_query_credential(request, name, variable);
+                    }
+                    Some("cookie") => {
+                        let Some(value) = session.get_cookie(name).cloned().or_else(|| variable(&[name, scheme_name])) else {
+                            return false;
+                        };
+                        found.add_cookie(name, &value);
+                    }
+                    _ => return false,
+                }
+            }
+            Some("http") => match scheme.get("scheme").and_then(JsonValue::as_str).map(str::to_ascii_lowercase).as_deref() {
+                Some("basic") => {
+                    let (Some(user), Some(password)) = (variable(&["username"]), variable(&["password"])) else {
+                        return false;
+                    };
+                    let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+                    set_header(found, "Authorization", &format!("Basic {}", credentials));
+                }
+                _ => match bearer() {
+                    Some(value) => set_header(found, "Authorization", &value),
+                    None => return false,
+                },
+            },
+            Some("oauth2") | Some("openIdConnect") => match bearer() {
+                Some(value) => set_header(found, "Authorization", &value),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// An example value for a schema: its own example, default or first enum
+    /// value, or one built from its type
+    fn schema_example(&self, schema: &JsonValue, depth: usize) -> JsonValue {
+        let schema = self.resolve(schema);
+        if let Some(example) = schema.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = schema.get("examples").and_then(JsonValue::as_array).and_then(|e| e.first()) {
+            return example.clone();
+        }
+        if let Some(default) = schema.get("default") {
+            return default.clone();
+        }
+        if let Some(first) = schema.get("enum").and_then(JsonValue::as_array).and_then(|e| e.first()) {
+            return first.clone();
+        }
+        if depth >= MAX_SCHEMA_DEPTH {
+            return JsonValue::Null;
+        }
+
+        if let Some(parts) = schema.get("allOf").and_then(JsonValue::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                match self.schema_example(part, depth + 1) {
+                    JsonValue::Object(map) => merged.extend(map),
+                    other if parts.len() == 1 => return other,
+                    _ => {}
+                }
+            }
+            return JsonValue::Object(merged);
+        }
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(JsonValue::as_array).and_then(|s| s.first()) {
+                return self.schema_example(first, depth + 1);
+            }
+        }
+
+        let schema_type = match schema.get("type") {
+            Some(JsonValue::Array(types)) => types.iter().filter_map(JsonValue::as_str).find(|t| *t != "null"),
+            Some(value) => value.as_str(),
+            None if schema.get("properties").is_some() => Some("object"),
+            None => None,
+        };
+        match schema_type {
+            Some("object") => JsonValue::Object(
+                schema
+                    .get("properties")
+                    .and_then(JsonValue::as_object)
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), self.schema_example(property, depth + 1)))
+                    .collect(),
+            ),
+            Some("array") => JsonValue::Array(
+                schema.get("items").map(|items| vec![self.schema_example(items, depth + 1)]).unwrap_or_default(),
+            ),
+            Some("integer") | Some("number") => JsonValue::from(1),
+            Some("boolean") => JsonValue::Bool(true),
+            _ => JsonValue::String(
+                match schema.get("format").and_then(JsonValue::as_str) {
+                    Some("uuid") => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    Some("date") => "2024-01-01",
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("email") => "user@example.com",
+                    Some("uri") | Some("url") => "https://example.com",
+                    Some("ipv4") => "127.0.0.1",
+                    Some("byte") => "dGVzdA==",
+                    _ => "test",
+                }
+                .to_string(),
+            ),
+        }
+    }
+
+    /// Follows `$ref`s within the document
+    fn resolve<'a>(&'a self, mut value: &'a JsonValue) -> &'a JsonValue {
+        for _ in 0..MAX_REF_HOPS {
+            let Some(reference) = value.get("$ref").and_then(JsonValue::as_str) else {
+                break;
+            };
+            match reference.strip_prefix('#').and_then(|pointer| self.document.pointer(pointer)) {
+                Some(target) => value = target,
+                None => {
+                    log::warn!("Cannot resolve reference {}", reference);
+                    break;
+                }
+            }
+        }
+        value
+    }
+}
+
+/// Value of the first entry of an OpenAPI `examples` map
+fn first_named_example<'a>(
+    examples: Option<&'a JsonValue>,
+    resolve: impl Fn(&'a JsonValue) -> &'a JsonValue,
+) -> Option<JsonValue> {
+    let (_, example) = examples?.as_object()?.iter().next()?;
+    resolve(example).get("value").cloned()
+}
+
+/// Sets a session header, replacing any existing one with the same name in any case
+fn set_header(session: &mut Session, name: &str, value: &str) {
+    session.headers.retain(|key, _| !key.eq_ignore_ascii_case(name));
+    session.headers.insert(name.to_string(), value.to_string());
+}
+
+/// A simple XML rendering of an example: objects become child elements and
+/// array items repeat their parent element
+fn to_xml(name: &str, value: &JsonValue) -> String {
+    match value {
+        JsonValue::Object(map) => {
+            let children: String = map.iter().map(|(key, child)| to_xml(key, child)).collect();
+            format!("<{0}>{1}</{0}>", name, children)
+        }
+        JsonValue::Array(items) => items.iter().map(|item| to_xml(name, item)).collect(),
+        other => {
+            let text = scalar_string(other).replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            format!("<{0}>{1}</{0}>", name, text)
+        }
+    }
+}
+
+/// Escapes a key for use in a JSON pointer
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::injection::InjectionLocation;
+    use crate::core::safety::RiskLevel;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info: { title: Shop API, version: "1.0" }
+servers:
+  - url: https://{region}.shop.example.com/api/v1
+    variables: { region: { default: eu } }
+security:
+  - apiKey: []
+paths:
+  /users/{username}/orders:
+    parameters:
+      - { name: username, in: path, required: true, schema: { type: string } }
+    get:
+      parameters:
+        - { name: status, in: query, schema: { type: string, enum: [open, closed] } }
+        - { name: limit, in: query, schema: { type: integer } }
+        - { name: X-Trace, in: header, schema: { type: string, format: uuid } }
+      responses: { "200": { description: ok } }
+    post:
+      security:
+        - bearer: []
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Order" }
+      responses: { "201": { description: created } }
+  /health:
+    get:
+      security: []
+      responses: { "200": { description: ok } }
+components:
+  schemas:
+    Order:
+      type: object
+      properties:
+        item: { type: string, example: book }
+        quantity: { type: integer }
+        tags: { type: array, items: { type: string } }
+        parent: { $ref: "#/components/schemas/Order" }
+  securitySchemes:
+    apiKey: { type: apiKey, in: header, name: X-API-Key }
+    bearer: { type: http, scheme: bearer }
+"##;
+
+    #[test]
+    fn test_openapi_endpoints() {
+        let spec = OpenApiSpec::parse(SPEC).unwrap();
+        assert_eq!(spec.server_url().as_deref(), Some("https://eu.shop.example.com/api/v1"));
+
+        let mut session = Session::new("https://eu.shop.example.com");
+        session.headers.insert("X-API-Key".to_string(), "k-123".to_string());
+        session.variables.insert("token".to_string(), "t-456".to_string());
+        let site_map = spec.to_site_map(None, &mut session).unwrap();
+        assert_eq!(site_map.endpoints.len(), 3);
+        assert_eq!(site_map.session.as_deref(), Some(session.id.as_str()));
+        assert_eq!(session.get_header("Authorization").map(String::as_str), Some("Bearer t-456"));
+        let find = |method: &str, path: &str| {
+            site_map
+                .endpoints
+                .iter()
+                .find(|e| e.request.method == method && e.request.url.contains(path))
+                .unwrap()
+        };
+
+        let list = find("GET", "/orders");
+        assert_eq!(list.request.method, "GET");
+        assert_eq!(list.request.url, "https://eu.shop.example.com/api/v1/users/test/orders?status=open&limit=1");
+        assert_eq!(list.request.header("X-Trace"), Some("3fa85f64-5717-4562-b3fc-2c963f66afa6"));
+        assert_eq!(list.request.header("x-api-key"), None);
+        assert_eq!(list.request.risk(), RiskLevel::ReadOnly);
+        assert_eq!(list.parameters, vec!["username", "status", "limit"]);
+        assert_eq!(list.injection_points.len(), 1);
+        assert_eq!(list.injection_points[0].location, InjectionLocation::PathSegment);
+        assert_eq!(list.injection_points[0].name, "username");
+        assert_eq!(
+            list.injection_points[0].inject(&list.request, "x'").url,
+            "https://eu.shop.example.com/api/v1/users/x%27/orders?status=open&limit=1"
+        );
+
+        let create = find("POST", "/orders");
+        assert_eq!(create.request.header("Authorization"), None);
+        assert_eq!(create.request.risk(), RiskLevel::StateChanging);
+        let mut sent = create.request.clone();
+        session.apply(&mut sent);
+        assert_eq!(sent.header("Authorization"), Some("Bearer t-456"));
+        let body: JsonValue = serde_json::from_str(create.request.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["item"], "book");
+        assert_eq!(body["quantity"], 1);
+        assert_eq!(body["tags"][0], "test");
+        assert!(body["parent"].is_object());
+
+        let health = find("GET", "/health");
+        assert_eq!(health.request.url, "https://eu.shop.example.com/api/v1/health");
+        assert_eq!(health.request.header("Authorization"), None);
+    }
+
+    #[test]
+    fn test_relative_server_and_versions() {
+        let spec = OpenApiSpec::parse(r#"{"openapi":"3.1.0","servers":[{"url":"/v2"}],"paths":{"/ping":{"get":{}}}}"#).unwrap();
+        let mut session = Session::new("http://localhost:8080");
+        assert!(spec.to_site_map(None, &mut session).is_err());
+        let site_map = spec.to_site_map(Some("http://localhost:8080"), &mut session).unwrap();
+        assert_eq!(site_map.endpoints[0].request.url, "http://localhost:8080/v2/ping");
+
+        assert!(OpenApiSpec::parse(r#"{"swagger":"2.0"}"#).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+// src/import/postman.rs
+
+// Postman collection (v2.0 and v2.1) import
+
+use crate::core::http_request::HttpRequest;
+use crate::core::injection::InjectionPoint;
+use crate::core::session::Session;
+use crate::core::site_map::{Endpoint, EndpointSource, SiteMap};
+use crate::import::{add_query_credential, merge_session, scalar_string, take_credentials};
+use base64::Engine;
+use chrono::Utc;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::Path;
+use url::Url;
+use uuid::Uuid;
+
+/// Folders nested deeper than this are not imported
+const MAX_FOLDER_DEPTH: usize = 16;
+
+/// Substitution passes, so variables may refer to other variables
+const MAX_VARIABLE_PASSES: usize = 4;
+
+/// A Postman collection, kept as JSON since requests come in several shapes
+pub struct PostmanCollection {
+    document: JsonValue,
+}
+
+/// A request item with the auth it inherits from its folders
+struct Item<'a> {
+    path: Vec<&'a str>,
+    request: &'a JsonValue,
+    auth: Option<&'a JsonValue>,
+}
+
+impl PostmanCollection {
+    /// Parses a collection export; only the v2 formats are supported
+    pub fn parse(source: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let document: JsonValue = serde_json::from_str(source).map_err(|e| format!("not valid JSON: {}", e))?;
+        if document.get("requests").is_some() && document.get("item").is_none() {
+            return Err("Postman v1 collections are not supported; export as v2.1".into());
+        }
+        if !document.get("item").is_some_and(JsonValue::is_array) {
+            return Err("missing `item` list".into());
+        }
+        Ok(Self { document })
+    }
+
+    /// Loads a collection file
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|e| format!("Invalid Postman collection {}: {}", path.display(), e).into())
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.document.pointer("/info/name").and_then(JsonValue::as_str)
+    }
+
+    /// Turns every request into an endpoint, filling `{{variables}}` from the
+    /// collection and `session.variables` (which take precedence). The
+    /// collection's auth, cookies and credential headers are moved to
+    /// `session` where it has no value of its own; the site map refers to the
+    /// session, which is applied when requests are sent.
+    pub fn to_site_map(&self, session: &mut Session) -> Result<SiteMap, Box<dyn std::error::Error + Send + Sync>> {
+        let mut variables: HashMap<String, String> = HashMap::new();
+        for variable in self.document.get("variable").and_then(JsonValue::as_array).into_iter().flatten() {
+            if variable.get("disabled").and_then(JsonValue::as_bool) == Some(true) {
+                continue;
+            }
+            if let Some(key) = variable.get("key").and_then(JsonValue::as_str) {
+                variables.insert(key.to_string(), variable.get("value").map(scalar_string).unwrap_or_default());
+            }
+        }
+        variables.extend(session.variables.clone());
+
+        let mut items = Vec::new();
+        collect_items(&self.document, Vec::new(), self.document.get("auth"), &mut items, 0);
+
+        let mut found = Session::new(&session.target);
+        if let Some(auth) = self.document.get("auth") {
+            let mut request = HttpRequest::get("http://localhost/");
+            apply_auth(&mut request, auth, &variables, &mut found);
+            take_credentials(&mut request, &mut found);
+        }
+
+        let mut endpoints = Vec::new();
+        for item in &items {
+            match item_endpoint(item, &variables, &mut found) {
+                Ok(endpoint) => endpoints.push(endpoint),
+                Err(e) => log::warn!("Skipping request '{}': {}", item.path.join(" / "), e),
+            }
+        }
+        // The user's own credentials replace those saved in the collection
+        merge_session(session, &found);
+
+        let target = endpoints
+            .first()
+            .and_then(|endpoint| Url::parse(&endpoint.request.url).ok())
+            .map(|url| url.origin().ascii_serialization())
+            .ok_or("the collection contains no requests with a valid URL")?;
+        let mut site_map = SiteMap::new(&target);
+        site_map.session = Some(session.id.clone());
+        for endpoint in endpoints {
+            site_map.add(endpoint);
+        }
+
+        log::info!(
+            "Imported {} endpoints from Postman collection{}",
+            site_map.endpoints.len(),
+            self.name().map(|name| format!(" '{}'", name)).unwrap_or_default()
+        );
+        Ok(site_map)
+    }
+}
+
+/// Walks folders depth-first, passing their auth down to the requests
+fn collect_items<'a>(
+    folder: &'a JsonValue,
+    path: Vec<&'a str>,
+    auth: Option<&'a JsonValue>,
+    items: &mut Vec<Item<'a>>,
+    depth: usize,
+) {
+    if depth > MAX_FOLDER_DEPTH {
+        return;
+    }
+    for child in folder.get("item").and_then(JsonValue::as_array).into_iter().flatten() {
+        let mut child_path = path.clone();
+        child_path.push(child.get("name").and_then(JsonValue::as_str).unwrap_or("?"));
+        let child_auth = child.get("auth").filter(|a| !a.is_null()).or(auth);
+        match child.get("request") {
+            Some(request) => {
+                let auth = request.get("auth").filter(|a| !a.is_null()).or(child_auth);
+                items.push(Item { path: child_path, request, auth });
+            }
+            None => collect_items(child, child_path, child_auth, items, depth + 1),
+        }
+    }
+}
+
+/// The endpoint of one request; its credentials are moved to `found`, keeping
+/// those found first
+fn item_endpoint(
+    item: &Item,
+    variables: &HashMap<String, String>,
+    found: &mut Session,
+) -> Result<Endpoint, Box<dyn std::error::Error + Send + Sync>> {
+    let definition = item.request;
+    let (raw_url, path_variables) = match definition {
+        JsonValue::String(url) => (url.clone(), Vec::new()),
+        _ => match definition.get("url") {
+            Some(JsonValue::String(url)) => (url.clone(), Vec::new()),
+            Some(url) => (url_text(url), key_values(url.get("variable"))),
+            None => return Err("no URL".into()),
+        },
+    };
+
+    let mut raw_url = substitute(&raw_url, variables);
+    if let Some(start) = raw_url.find("{{") {
+        let name = raw_url[start + 2..].split("}}").next().unwrap_or_default();
+        return Err(format!("undefined variable '{}' in the URL; pass it with --var", name.trim()).into());
+    }
+    if !raw_url.contains("://") {
+        raw_url = format!("http://{}", raw_url);
+    }
+    let mut url = Url::parse(&raw_url).map_err(|e| format!("invalid URL '{}': {}", raw_url, e))?;
+
+    // `:name` segments are path variables
+    let mut path_points = Vec::new();
+    let segments: Vec<String> = url.path_segments().map(|s| s.map(str::to_string).collect()).unwrap_or_default();
+    let mut filled = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        match segment.strip_prefix(':').filter(|name| !name.is_empty()) {
+            Some(name) => {
+                let value = path_variables
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| substitute(value, variables))
+                    .unwrap_or_else(|| "1".to_string());
+                path_points.push(InjectionPoint::path_segment(index, name, &value));
+                filled.push(value);
+            }
+            None => filled.push(segment.clone()),
+        }
+    }
+    if !path_points.is_empty()
+        && let Ok(mut path) = url.path_segments_mut()
+    {
+        path.clear().extend(&filled);
+    }
+
+    let mut request = HttpRequest::get(url.as_str());
+    request.method = definition.get("method").and_then(JsonValue::as_str).unwrap_or("GET").to_ascii_uppercase();
+    for (name, value) in headers(definition.get("header")) {
+        request.set_header(&name, &substitute(&value, variables));
+    }
+    if let Some(body) = definition.get("body") {
+        set_body(&mut request, body, variables);
+    }
+    if let Some(auth) = item.auth {
+        apply_auth(&mut request, auth, variables, found);
+    }
+    let mut credentials = Session::new(&found.target);
+    take_credentials(&mut request, &mut credentials);
+    merge_session(found, &credentials);
+
+    let mut endpoint = Endpoint::new(request, EndpointSource::Postman, 0);
+    endpoint.parameters.splice(0..0, path_points.iter().map(|p| p.name.clone()));
+    endpoint.injection_points = path_points;
+    Ok(endpoint)
+}
+
+/// The URL of a structured Postman URL object, from `raw` or its parts
+fn url_text(url: &JsonValue) -> String {
+    if let Some(raw) = url.get("raw").and_then(JsonValue::as_str) {
+        return raw.to_string();
+    }
+    let join = |value: Option<&JsonValue>, separator: &str| match value {
+        Some(JsonValue::Array(parts)) => parts.iter().map(scalar_string).collect::<Vec<_>>().join(separator),
+        Some(value) => scalar_string(value),
+        None => String::new(),
+    };
+    let protocol = url.get("protocol").and_then(JsonValue::as_str).unwrap_or("http");
+    let mut raw = format!("{}://{}", protocol, join(url.get("host"), "."));
+    if let Some(port) = url.get("port").map(scalar_string) {
+        raw = format!("{}:{}", raw, port);
+    }
+    raw = format!("{}/{}", raw, join(url.get("path"), "/"));
+    let query: Vec<String> = key_values(url.get("query")).iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    if !query.is_empty() {
+        raw = format!("{}?{}", raw, query.join("&"));
+    }
+    raw
+}
+
+/// Sets the body of a request from a Postman body definition; files are left
+/// out of form-data
+fn set_body(request: &mut HttpRequest, body: &JsonValue, variables: &HashMap<String, String>) {
+    let encode = |pairs: Vec<(String, String)>| {
+        let pairs = pairs.into_iter().map(|(k, v)| (substitute(&k, variables), substitute(&v, variables)));
+        url::form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish()
+    };
+    let (content_type, text) = match body.get("mode").and_then(JsonValue::as_str) {
+        Some("raw") => {
+            let content_type = match body.pointer("/options/raw/language").and_then(JsonValue::as_str) {
+                Some("json") => "application/json",
+                Some("xml") => "application/xml",
+                Some("html") => "text/html",
+                _ => "text/plain",
+            };
+            (content_type, substitute(body.get("raw").and_then(JsonValue::as_str).unwrap_or_default(), variables))
+        }
+        Some("urlencoded") => ("application/x-www-form-urlencoded", encode(key_values(body.get("urlencoded")))),
+        Some("formdata") => {
+            let fields = body
+                .get("formdata")
+                .and_then(JsonValue::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|field| field.get("type").and_then(JsonValue::as_str) != Some("file"));
+            let pairs: Vec<(String, String)> = key_values(Some(&JsonValue::Array(fields.cloned().collect())))
+                .into_iter()
+                .map(|(k, v)| (substitute(&k, variables), substitute(&v, variables)))
+                .collect();
+            request.set_multipart_body(&pairs);
+            return;
+        }
+        Some("graphql") => {
+            let query = body.pointer("/graphql/query").and_then(JsonValue::as_str).unwrap_or_default();
+            let variables_json = body
+                .pointer("/graphql/variables")
+                .and_then(JsonValue::as_str)
+                .and_then(|text| serde_json::from_str(&substitute(text, variables)).ok())
+                .unwrap_or_else(|| serde_json::json!({}));
+            let document = serde_json::json!({ "query": substitute(query, variables), "variables": variables_json });
+            ("application/json", document.to_string())
+        }
+        _ => return,
+    };
+    if request.header("Content-Type").is_none() {
+        request.set_header("Content-Type", content_type);
+    }
+    request.body = Some(text);
+}
+
+/// Applies a Postman auth definition (bearer, basic, API key or an OAuth 2
+/// token); a query API key becomes a placeholder whose value goes to `found`
+fn apply_auth(request: &mut HttpRequest, auth: &JsonValue, variables: &HashMap<String, String>, found: &mut Session) {
+    let Some(auth_type) = auth.get("type").and_then(JsonValue::as_str) else {
+        return;
+    };
+    let parameter = |key: &str| auth_parameter(auth, auth_type, key).map(|value| substitute(&value, variables));
+
+    match auth_type {
+        "bearer" => {
+            if let Some(token) = parameter("token") {
+                request.set_header("Authorization", &format!("Bearer {}", token));
+            }
+        }
+        "oauth2" => {
+            if let Some(token) = parameter("accessToken") {
+                let prefix = parameter("headerPrefix").filter(|p| !p.is_empty()).unwrap_or_else(|| "Bearer".to_string());
+                request.set_header("Authorization", &format!("{} {}", prefix, token));
+            }
+        }
+        "basic" => {
+            let user = parameter("username").unwrap_or_default();
+            let password = parameter("password").unwrap_or_default();
+            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+            request.set_header("Authorization", &format!("Basic {}", credentials));
+        }
+        "apikey" => {
+            let (Some(name), Some(value)) = (parameter("key"), parameter("value")) else {
+                return;
+            };
+            if parameter("in").as_deref() == Some("query") {
+                add_query_credential(request, &name, &name);
+                found.variables.entry(name).or_insert(value);
+            } else {
+                request.set_header(&name, &value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A parameter of an auth definition, stored as a list of key/value pairs in
+/// v2.1 and as an object in v2.0
+fn auth_parameter(auth: &JsonValue, auth_type: &str, key: &str) -> Option<String> {
+    match auth.get(auth_type)? {
+        JsonValue::Array(parameters) => parameters
+            .iter()
+            .find(|p| p.get("key").and_then(JsonValue::as_str) == Some(key))
+            .and_then(|p| p.get("value"))
+            .map(scalar_string),
+        JsonValue::Object(parameters) => parameters.get(key).map(scalar_string),
+        _ => None,
+    }
+}
+
+/// Enabled headers, given as a list of key/value pairs or as raw header lines
+fn headers(value: Option<&JsonValue>) -> Vec<(String, String)> {
+    match value {
+        Some(JsonValue::String(lines)) => lines
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect(),
+        other => key_values(other),
+    }
+}
+
+/// Enabled entries of a Postman key/value list
+fn key_values(value: Option<&JsonValue>) -> Vec<(String, String)> {
+    value
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.get("disabled").and_then(JsonValue::as_bool) != Some(true))
+        .filter_map(|entry| {
+            let key = entry.get("key").and_then(JsonValue::as_str)?;
+            Some((key.to_string(), entry.get("value").map(scalar_string).unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Replaces `{{name}}` with the variable's value and Postman's dynamic
+/// variables (`{{$guid}}`, `{{$timestamp}}`, ...) with fresh values; unknown
+/// names are left in place
+fn substitute(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut text = text.to_string();
+    for _ in 0..MAX_VARIABLE_PASSES {
+        if !text.contains("{{") {
+            break;
+        }
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find("{{") {
+            let Some(length) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + length].trim();
+            result.push_str(&rest[..start]);
+            match variables.get(name).cloned().or_else(|| dynamic_variable(name)) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..start + 4 + length]),
+            }
+            rest = &rest[start + 4 + length..];
+        }
+        result.push_str(rest);
+        if result == text {
+            break;
+        }
+        text = result;
+    }
+    text
+}
+
+fn dynamic_variable(name: &str) -> Option<String> {
+    match name {
+        "$guid" | "$randomUUID" => Some(Uuid::new_v4().to_string()),
+        "$timestamp" => Some(Utc::now().timestamp().to_string()),
+        "$isoTimestamp" => Some(Utc::now().to_rfc3339()),
+        "$randomInt" => Some((Utc::now().timestamp_subsec_millis() % 1000).to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::safety::RiskLevel;
+
+    const COLLECTION: &str = r#"{
+        "info": {"name": "Shop", "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"},
+        "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}", "type": "string"}]},
+        "variable": [{"key": "baseUrl", "value": "https://api.shop.example.com/v1"}, {"key": "token", "value": "saved"}],
+        "item": [
+            {"name": "Users", "item": [
+                {"name": "Get user", "request": {
+                    "method": "GET",
+                    "header": [{"key": "X-Trace", "value": "{{$guid}}"}, {"key": "X-Old", "value": "1", "disabled": true}],
+                    "url": {"raw": "{{baseUrl}}/users/:id?fields=name",
+                            "variable": [{"key": "id", "value": "42"}]}}}
+            ]},
+            {"name": "Login", "request": {
+                "method": "POST",
+                "auth": {"type": "noauth"},
+                "body": {"mode": "urlencoded", "urlencoded": [{"key": "user", "value": "bob"}, {"key": "pass", "value": "x"}]},
+                "url": "{{baseUrl}}/login"}},
+            {"name": "Legacy", "request": {
+                "method": "GET",
+                "auth": {"type": "apikey", "apikey": [{"key": "key", "value": "api_key"}, {"key": "value", "value": "k1"},
+                                                      {"key": "in", "value": "query"}]},
+                "url": "{{baseUrl}}/legacy/{{missing}}"}},
+            {"name": "Report", "request": {
+                "method": "GET",
+                "auth": {"type": "apikey", "apikey": [{"key": "key", "value": "api_key"}, {"key": "value", "value": "k1"},
+                                                      {"key": "in", "value": "query"}]},
+                "url": "{{baseUrl}}/report"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_postman_endpoints_and_session() {
+        let collection = PostmanCollection::parse(COLLECTION).unwrap();
+        let mut session = Session::new("");
+        session.variables.insert("token".to_string(), "fresh".to_string());
+        let site_map = collection.to_site_map(&mut session).unwrap();
+        assert_eq!(site_map.target, "https://api.shop.example.com");
+        assert_eq!(site_map.endpoints.len(), 3);
+        assert_eq!(site_map.session.as_deref(), Some(session.id.as_str()));
+        assert_eq!(session.get_header("Authorization").map(String::as_str), Some("Bearer fresh"));
+
+        let user = &site_map.endpoints[0];
+        assert_eq!(user.request.url, "https://api.shop.example.com/v1/users/42?fields=name");
+        assert_eq!(user.request.header("Authorization"), None);
+        assert_eq!(user.request.header("X-Trace").map(str::len), Some(36));
+        assert_eq!(user.request.header("X-Old"), None);
+        assert_eq!(user.parameters, vec!["id", "fields"]);
+        assert_eq!(user.injection_points[0].inject(&user.request, "x'").url, "https://api.shop.example.com/v1/users/x%27?fields=name");
+
+        let login = &site_map.endpoints[1];
+        assert_eq!(login.request.method, "POST");
+        assert_eq!(login.request.risk(), RiskLevel::StateChanging);
+        assert_eq!(login.request.body.as_deref(), Some("user=bob&pass=x"));
+        assert_eq!(login.request.header("Authorization"), None);
+
+        let mut report = site_map.endpoints[2].request.clone();
+        assert_eq!(report.url, "https://api.shop.example.com/v1/report?api_key={{api_key}}");
+        session.apply(&mut report);
+        assert_eq!(report.url, "https://api.shop.example.com/v1/report?api_key=k1");
+        assert_eq!(report.header("Authorization"), Some("Bearer fresh"));
+
+        assert!(PostmanCollection::parse(r#"{"requests": []}"#).is_err());
+    }
+}
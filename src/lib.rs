@@ -1,3 +1,6 @@
 pub mod core;
 pub mod modules;
 pub mod cli;
+pub mod report;
+pub mod payloads;
+pub mod import;
@@ -1,17 +1,34 @@
-mod core;
-mod modules;
-mod cli;
-
-use crate::core::{Database, ModuleHandler, Logger, ScannerEngine};
-use std::sync::Arc;
+use clap::Parser;
+use redlines_x::cli::{self, Cli};
+use redlines_x::core::{AuditLog, Database, ModuleHandler, Logger};
+use redlines_x::modules;
+use redlines_x::modules::auxiliary::param_miner::ParameterMiner;
+use redlines_x::payloads::PayloadLibrary;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(code) => code,
+        Err(e) => {
+            log::error!("{}", e);
+            eprintln!("Error: {}", e);
+            ExitCode::from(cli::EXIT_ERROR)
+        }
+    }
+}
+
+async fn run() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Initialize logging
-    Logger::init();
+    Logger::init_with(&cli.log_config())?;
+    AuditLog::init(&cli.audit_log)?;
+    PayloadLibrary::init_user_dirs(&cli.payload_dirs)?;
+    ParameterMiner::init_user_wordlist(cli.param_wordlist.as_deref()).map_err(|e| e.to_string())?;
     
-    println!("🚀 Redlines X - Advanced Security Scanner");
-    println!("=========================================");
+    eprintln!("🚀 Redlines X - Advanced Security Scanner");
+    eprintln!("=========================================");
     
     // Initialize database
     let db = Database::new()?;
@@ -21,10 +38,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut module_handler = ModuleHandler::new();
     
     // Load and register modules
-    let modules = modules::load_all_modules();
-    for module in modules {
-        module_handler.register_module(module);
-    }
+    modules::load_all_modules(&mut module_handler);
     
     Logger::info(&format!("Loaded {} modules", module_handler.module_count()));
     
@@ -35,11 +49,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
     
-    // Initialize scanner engine
-    let scanner_engine = ScannerEngine::new();
-    
     // Start CLI with all components
-    cli::run_cli(module_handler, db, scanner_engine).await?;
-    
-    Ok(())
+    cli::run_cli(cli, module_handler, db).await
 }
\ No newline at end of file
@@ -1,3 +1,668 @@
 // src/modules/auxiliary/analyzer.rs
 
 // Response analyzer
+
+use crate::core::http_request::HttpRequest;
+use crate::core::injection::InjectionPoint;
+use crate::core::request_engine::RequestEngine;
+use crate::core::safety::RiskLevel;
+use crate::modules::auxiliary::signatures::{SignatureDatabase, SignatureMatch};
+use regex::Regex;
+use reqwest::Response;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Headers whose values change on every response and say nothing about the input
+const VOLATILE_HEADERS: &[&str] = &[
+    "date", "expires", "last-modified", "etag", "age", "content-length", "set-cookie",
+    "x-request-id", "x-correlation-id", "x-runtime", "cf-ray", "server-timing",
+];
+
+/// Latency differences below this many seconds are never treated as significant
+pub(crate) const MIN_TIME_NOISE: f64 = 0.5;
+
+/// Placeholder that replaces stripped dynamic content
+const DYNAMIC: &str = "\u{1}";
+
+/// Content that differs between two loads of the same page: dates and times,
+/// long numbers such as Unix timestamps, and tokens such as CSRF values,
+/// nonces, session ids and UUIDs
+static DYNAMIC_CONTENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)
+        \d{4}-\d{2}-\d{2}(?:[t\s]\d{2}:\d{2}(?::\d{2})?(?:\.\d+)?(?:z|[+-]\d{2}:?\d{2})?)?
+        | \b(?:mon|tue|wed|thu|fri|sat|sun),\s\d{1,2}\s\w{3}\s\d{4}\s\d{2}:\d{2}:\d{2}(?:\s\w+)?
+        | \b\d{1,2}:\d{2}:\d{2}(?:\.\d+)?\b
+        | \b\d{10,}\b
+        | \b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b
+        | \b[0-9a-f]{16,}\b",
+    )
+    .expect("dynamic content pattern is valid")
+});
+
+/// Runs of token characters; those long enough and mixing letters and digits
+/// are treated as tokens
+static TOKEN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9+/_-]{16,}={0,2}").expect("token pattern is valid"));
+
+/// Where an injected value shows up in a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReflectionContext {
+    /// Between tags, or inside a comment or text-only element
+    HtmlText,
+    DoubleQuotedAttribute,
+    SingleQuotedAttribute,
+    /// An unquoted attribute value, or among attribute names
+    UnquotedAttribute,
+    /// Inside a `<script>` block
+    Script,
+    /// Inside a string of a JSON response
+    JsonString,
+    /// Outside the strings of a JSON response, e.g. as a number
+    JsonLiteral,
+    /// In a response header value
+    Header,
+}
+
+impl fmt::Display for ReflectionContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReflectionContext::HtmlText => "HTML text",
+            ReflectionContext::DoubleQuotedAttribute => "double-quoted attribute",
+            ReflectionContext::SingleQuotedAttribute => "single-quoted attribute",
+            ReflectionContext::UnquotedAttribute => "unquoted attribute",
+            ReflectionContext::Script => "script block",
+            ReflectionContext::JsonString => "JSON string",
+            ReflectionContext::JsonLiteral => "JSON literal",
+            ReflectionContext::Header => "response header",
+        })
+    }
+}
+
+/// Status code, headers, body and latency of a response, kept for comparisons
+#[derive(Debug, Clone, Default)]
+pub struct ResponseSnapshot {
+    pub status: u16,
+    /// Header names are lowercase
+    pub headers: HashMap<String, String>,
+    pub body: String,
+    pub elapsed: Duration,
+}
+
+impl ResponseSnapshot {
+    /// Reads the whole response; `started` is when the request was sent
+    pub async fn capture(response: Response, started: Instant) -> Self {
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+            .collect();
+        let body = response.text().await.unwrap_or_default();
+
+        Self { status, headers, body, elapsed: started.elapsed() }
+    }
+
+    pub fn summary(&self) -> String {
+        format!("status {}, {} bytes", self.status, self.body.len())
+    }
+}
+
+/// What a page normally looks like, learned from several loads of it.
+///
+/// Tokens present in every load form the stable part of the page; the rest is
+/// dynamic (counters, ads, rotating content). Body length and latency are kept
+/// as mean and standard deviation so that only deviations outside the normal
+/// noise count as changes.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// First load, for evidence and where a single response is needed
+    pub response: ResponseSnapshot,
+    /// Number of loads the baseline was built from
+    pub samples: usize,
+    /// All loads returned the same status code
+    pub status_stable: bool,
+    pub length_mean: f64,
+    pub length_std_dev: f64,
+    pub time_mean: f64,
+    pub time_std_dev: f64,
+    /// Normalized tokens present in every load, with their minimum count
+    stable_tokens: HashMap<String, usize>,
+    stable_token_count: usize,
+    /// Most tokens that varied in a single load
+    max_variable_tokens: usize,
+    /// Non-volatile headers with the same value in every load
+    stable_headers: HashMap<String, String>,
+}
+
+impl Baseline {
+    /// Builds a baseline from loads of the same request; needs at least one
+    pub fn from_samples(samples: &[ResponseSnapshot]) -> Self {
+        let response = samples.first().cloned().unwrap_or_default();
+
+        let token_counts: Vec<HashMap<String, usize>> = samples.iter().map(|s| token_counts(&s.body)).collect();
+        let mut stable_tokens = token_counts.first().cloned().unwrap_or_default();
+        for counts in token_counts.iter().skip(1) {
+            stable_tokens.retain(|token, count| match counts.get(token) {
+                Some(other) => {
+                    *count = (*count).min(*other);
+                    true
+                }
+                None => false,
+            });
+        }
+        let stable_token_count = stable_tokens.values().sum();
+        let max_variable_tokens = token_counts
+            .iter()
+            .map(|counts| counts.values().sum::<usize>() - stable_token_count)
+            .max()
+            .unwrap_or(0);
+
+        let stable_headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !VOLATILE_HEADERS.contains(&name.as_str()))
+            .filter(|(name, value)| samples.iter().all(|s| s.headers.get(*name) == Some(*value)))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        let lengths: Vec<f64> = samples.iter().map(|s| s.body.len() as f64).collect();
+        let times: Vec<f64> = samples.iter().map(|s| s.elapsed.as_secs_f64()).collect();
+        let (length_mean, length_std_dev) = mean_and_std_dev(&lengths);
+        let (time_mean, time_std_dev) = mean_and_std_dev(&times);
+
+        Self {
+            status_stable: samples.iter().all(|s| s.status == response.status),
+            response,
+            samples: samples.len(),
+            length_mean,
+            length_std_dev,
+            time_mean,
+            time_std_dev,
+            stable_tokens,
+            stable_token_count,
+            max_variable_tokens,
+            stable_headers,
+        }
+    }
+
+    /// Similarity of a body to the stable part of the page in `[0, 1]`
+    ///
+    /// As many unmatched tokens as varied in the noisiest baseline load are
+    /// forgiven, so normal dynamic content does not lower the score.
+    pub fn similarity(&self, body: &str) -> f64 {
+        let mut remaining = self.stable_tokens.clone();
+        let mut common = 0usize;
+        let mut total = 0usize;
+        for token in tokens(body) {
+            total += 1;
+            if let Some(count) = remaining.get_mut(&token)
+                && *count > 0
+            {
+                *count -= 1;
+                common += 1;
+            }
+        }
+
+        let extra = (total - common).saturating_sub(self.max_variable_tokens);
+        let denominator = self.stable_token_count + common + extra;
+        if denominator == 0 {
+            1.0
+        } else {
+            2.0 * common as f64 / denominator as f64
+        }
+    }
+
+    /// Returns true if a body length is within the normal variation
+    pub fn length_is_noise(&self, length: usize) -> bool {
+        (length as f64 - self.length_mean).abs() <= 3.0 * self.length_std_dev
+    }
+
+    /// Returns true if a latency (seconds) is within the normal variation
+    pub fn time_is_noise(&self, seconds: f64) -> bool {
+        (seconds - self.time_mean).abs() <= (3.0 * self.time_std_dev).max(MIN_TIME_NOISE)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "status {}, {:.0}\u{b1}{:.0} bytes, {:.3}\u{b1}{:.3}s over {} loads",
+            self.response.status, self.length_mean, self.length_std_dev, self.time_mean, self.time_std_dev, self.samples
+        )
+    }
+}
+
+/// Differences between a response and the baseline it is compared to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseComparison {
+    pub status_changed: bool,
+    /// Non-volatile headers that were stable in the baseline and are missing or
+    /// changed, or that the response adds, by lowercase name
+    pub changed_headers: Vec<String>,
+    /// Body length of the response minus the baseline mean, in bytes
+    pub length_delta: i64,
+    /// The body length is outside the baseline's normal variation
+    pub length_anomalous: bool,
+    /// Similarity of the normalized body to the stable part of the baseline in `[0, 1]`
+    pub similarity: f64,
+    /// Latency of the response minus the baseline mean, in seconds
+    pub time_delta: f64,
+    /// The latency is outside the baseline's normal variation
+    pub time_anomalous: bool,
+    /// The payload appears in the response but not in the baseline
+    pub reflected: bool,
+    /// Error signatures found in the response but not in the baseline
+    pub new_errors: Vec<SignatureMatch>,
+}
+
+/// Compares responses against a baseline, ignoring content that changes on
+/// every load and the reflected payload itself
+pub struct ResponseAnalyzer;
+
+impl ResponseAnalyzer {
+    /// Loads a request `samples` times (at least once) and builds its baseline
+    pub async fn fetch_baseline(
+        engine: &RequestEngine,
+        request: &HttpRequest,
+        samples: usize,
+        purpose: &str,
+    ) -> Result<Baseline, Box<dyn std::error::Error + Send + Sync>> {
+        let mut snapshots = Vec::with_capacity(samples.max(1));
+        for _ in 0..samples.max(1) {
+            let started = Instant::now();
+            let response = engine.send(request, purpose, RiskLevel::ReadOnly).await?;
+            snapshots.push(ResponseSnapshot::capture(response, started).await);
+        }
+        Ok(Baseline::from_samples(&snapshots))
+    }
+
+    /// Compares `response` to `baseline`; `payload` is the injected text, if any,
+    /// and its reflections are ignored when measuring similarity and length
+    pub fn compare(baseline: &Baseline, response: &ResponseSnapshot, payload: Option<&str>) -> ResponseComparison {
+        let mut changed_headers: Vec<String> = baseline
+            .stable_headers
+            .iter()
+            .filter(|(name, value)| response.headers.get(*name) != Some(*value))
+            .map(|(name, _)| name.clone())
+            .chain(
+                response
+                    .headers
+                    .keys()
+                    .filter(|name| !VOLATILE_HEADERS.contains(&name.as_str()))
+                    .filter(|name| !baseline.response.headers.contains_key(*name))
+                    .cloned(),
+            )
+            .collect();
+        changed_headers.sort();
+        changed_headers.dedup();
+
+        let body = match payload {
+            Some(payload) => strip_payload(&response.body, payload),
+            None => response.body.clone(),
+        };
+        let elapsed = response.elapsed.as_secs_f64();
+
+        ResponseComparison {
+            status_changed: baseline.response.status != response.status,
+            changed_headers,
+            length_delta: (body.len() as f64 - baseline.length_mean).round() as i64,
+            length_anomalous: !baseline.length_is_noise(body.len()),
+            similarity: baseline.similarity(&body),
+            time_delta: elapsed - baseline.time_mean,
+            time_anomalous: !baseline.time_is_noise(elapsed),
+            reflected: payload.is_some_and(|p| Self::is_reflected(p, &baseline.response.body, &response.body)),
+            new_errors: Self::find_new_errors(baseline, response),
+        }
+    }
+
+    /// Similarity of two bodies in `[0, 1]` once dynamic content is stripped
+    pub fn similarity(a: &str, b: &str) -> f64 {
+        similarity(&Self::normalize(a), &Self::normalize(b))
+    }
+
+    /// Replaces timestamps, long numbers and token-like strings with a placeholder
+    pub fn normalize(body: &str) -> String {
+        let body = DYNAMIC_CONTENT.replace_all(body, DYNAMIC);
+        TOKEN
+            .replace_all(&body, |caps: &regex::Captures| {
+                let token = &caps[0];
+                let mixed = token.bytes().any(|b| b.is_ascii_digit()) && token.bytes().any(|b| b.is_ascii_alphabetic());
+                if mixed { DYNAMIC.to_string() } else { token.to_string() }
+            })
+            .into_owned()
+    }
+
+    /// Returns true if the payload (raw or HTML-escaped) appears in the response
+    /// more often than in the baseline
+    pub fn is_reflected(payload: &str, baseline: &str, response: &str) -> bool {
+        if payload.trim().is_empty() {
+            return false;
+        }
+        payload_forms(payload)
+            .iter()
+            .any(|form| response.matches(form.as_str()).count() > baseline.matches(form.as_str()).count())
+    }
+
+    /// A random prefix that cannot occur in a page by chance; canaries built
+    /// from it end with a letter
+    pub fn canary_prefix() -> String {
+        format!("rlx{}", &uuid::Uuid::new_v4().simple().to_string()[..10])
+    }
+
+    /// A unique value that cannot occur in a page by chance
+    pub fn canary() -> String {
+        // The trailing letter keeps the canary from running into following digits
+        format!("{}z", Self::canary_prefix())
+    }
+
+    /// Sends the point's original value followed by a canary and classifies
+    /// where the canary comes back
+    pub async fn detect_reflections(
+        engine: &RequestEngine,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+    ) -> Result<Vec<ReflectionContext>, Box<dyn std::error::Error + Send + Sync>> {
+        let canary = Self::canary();
+        let request = point.inject(base, &format!("{}{}", point.original_value(), canary));
+        let started = Instant::now();
+        let response = engine.send(&request, "reflection-canary", RiskLevel::ReadOnly).await?;
+        let snapshot = ResponseSnapshot::capture(response, started).await;
+        Ok(Self::reflection_contexts(&canary, &snapshot))
+    }
+
+    /// The context of every occurrence of `canary` in a response, headers first;
+    /// empty if it is not reflected
+    pub fn reflection_contexts(canary: &str, response: &ResponseSnapshot) -> Vec<ReflectionContext> {
+        let mut names: Vec<&String> = response.headers.keys().collect();
+        names.sort();
+        let mut contexts: Vec<ReflectionContext> = names
+            .into_iter()
+            .filter(|name| response.headers[*name].contains(canary))
+            .map(|_| ReflectionContext::Header)
+            .collect();
+
+        let json = response.headers.get("content-type").is_some_and(|t| t.contains("json"))
+            || serde_json::from_str::<serde_json::Value>(&response.body).is_ok_and(|v| v.is_object() || v.is_array());
+        for (index, _) in response.body.match_indices(canary) {
+            let before = &response.body[..index];
+            contexts.push(match json {
+                true if in_json_string(before) => ReflectionContext::JsonString,
+                true => ReflectionContext::JsonLiteral,
+                false => html_context(before),
+            });
+        }
+        contexts
+    }
+
+    /// Distinct reflection contexts in order, e.g. `HTML text, script block`,
+    /// or `not reflected`
+    pub fn describe_reflections(contexts: &[ReflectionContext]) -> String {
+        let mut distinct: Vec<String> = Vec::new();
+        for context in contexts.iter().map(ToString::to_string) {
+            if !distinct.contains(&context) {
+                distinct.push(context);
+            }
+        }
+        if distinct.is_empty() { "not reflected".to_string() } else { distinct.join(", ") }
+    }
+
+    /// Error signatures in the response whose text is not already in the baseline
+    pub fn find_new_errors(baseline: &Baseline, response: &ResponseSnapshot) -> Vec<SignatureMatch> {
+        SignatureDatabase::builtin().scan_new(&baseline.response.body, &response.body)
+    }
+
+    /// Error signatures anywhere in the text
+    pub fn find_errors(text: &str) -> Vec<SignatureMatch> {
+        SignatureDatabase::builtin().scan(text)
+    }
+}
+
+/// Context at the end of an HTML prefix, found by following tags, attribute
+/// quotes, comments and script blocks from the start of the document
+fn html_context(before: &str) -> ReflectionContext {
+    enum State {
+        Text,
+        Comment,
+        Script,
+        /// `equals` is set between `=` and the start of the value
+        Tag { quote: Option<char>, equals: bool, script: bool },
+    }
+
+    let lower = before.to_ascii_lowercase();
+    let mut state = State::Text;
+    let mut chars = lower.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let rest = &lower[index..];
+        state = match state {
+            State::Text if rest.starts_with("<!--") => State::Comment,
+            State::Text if c == '<' && chars.peek().is_some_and(|(_, next)| next.is_ascii_alphabetic()) => {
+                let name: String = rest[1..].chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+                State::Tag { quote: None, equals: false, script: name == "script" }
+            }
+            State::Comment if rest.starts_with("-->") => State::Text,
+            State::Script if rest.starts_with("</script") => State::Text,
+            State::Tag { quote: Some(quote), script, .. } if c == quote => State::Tag { quote: None, equals: false, script },
+            State::Tag { quote: None, script, .. } if c == '>' => {
+                if script { State::Script } else { State::Text }
+            }
+            State::Tag { quote: None, script, .. } if c == '=' => State::Tag { quote: None, equals: true, script },
+            State::Tag { quote: None, equals: true, script } if c == '"' || c == '\'' => {
+                State::Tag { quote: Some(c), equals: false, script }
+            }
+            State::Tag { quote: None, equals: true, script } if !c.is_whitespace() => {
+                State::Tag { quote: None, equals: false, script }
+            }
+            other => other,
+        };
+    }
+
+    match state {
+        State::Text | State::Comment => ReflectionContext::HtmlText,
+        State::Script => ReflectionContext::Script,
+        State::Tag { quote: Some('"'), .. } => ReflectionContext::DoubleQuotedAttribute,
+        State::Tag { quote: Some(_), .. } => ReflectionContext::SingleQuotedAttribute,
+        State::Tag { quote: None, .. } => ReflectionContext::UnquotedAttribute,
+    }
+}
+
+/// Returns true if a JSON document prefix ends inside a string
+fn in_json_string(before: &str) -> bool {
+    let mut inside = false;
+    let mut escaped = false;
+    for c in before.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if inside => escaped = true,
+            '"' => inside = !inside,
+            _ => {}
+        }
+    }
+    inside
+}
+
+/// The payload as it may appear in a response: raw and HTML-escaped
+fn payload_forms(payload: &str) -> Vec<String> {
+    let escaped = payload
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;");
+    if escaped == payload {
+        vec![payload.to_string()]
+    } else {
+        vec![payload.to_string(), escaped]
+    }
+}
+
+/// Removes reflections of the payload so they do not count as a change
+fn strip_payload(body: &str, payload: &str) -> String {
+    if payload.trim().is_empty() {
+        return body.to_string();
+    }
+    payload_forms(payload)
+        .iter()
+        .fold(body.to_string(), |body, form| body.replace(form.as_str(), ""))
+}
+
+/// Normalized whitespace-separated tokens of a body
+fn tokens(body: &str) -> Vec<String> {
+    ResponseAnalyzer::normalize(body).split_whitespace().map(|t| t.to_string()).collect()
+}
+
+fn token_counts(body: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for token in tokens(body) {
+        *counts.entry(token).or_default() += 1;
+    }
+    counts
+}
+
+/// Mean and (population) standard deviation of the samples
+pub fn mean_and_std_dev(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/// Word-level similarity of two texts in `[0, 1]`, computed as the Dice
+/// coefficient of their token multisets
+fn similarity(a: &str, b: &str) -> f64 {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    let mut total = 0usize;
+    for token in a.split_whitespace() {
+        *counts.entry(token).or_default() += 1;
+        total += 1;
+    }
+
+    let mut common = 0usize;
+    for token in b.split_whitespace() {
+        total += 1;
+        if let Some(count) = counts.get_mut(token)
+            && *count > 0
+        {
+            *count -= 1;
+            common += 1;
+        }
+    }
+
+    if total == 0 {
+        1.0
+    } else {
+        2.0 * common as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(status: u16, body: &str) -> ResponseSnapshot {
+        ResponseSnapshot { status, body: body.to_string(), ..ResponseSnapshot::default() }
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("a b c", "a b c"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("a b", "c d"), 0.0);
+        assert!((similarity("a b c d", "a b c e") - 0.75).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_dynamic_content_is_ignored() {
+        let a = r#"<p>Welcome back</p> <input type="hidden" name="csrf" value="f3a9c1d27be84e6a"> <small>Rendered 2024-05-01T10:22:31Z in 12ms</small>"#;
+        let b = r#"<p>Welcome back</p> <input type="hidden" name="csrf" value="9b0e77aa31c24d58"> <small>Rendered 2024-05-01T10:22:35Z in 12ms</small>"#;
+        assert_eq!(ResponseAnalyzer::similarity(a, b), 1.0);
+        assert!(ResponseAnalyzer::similarity(a, "<p>Access denied</p>") < 0.5);
+
+        let token = "session dXNlcjQyOmFkbWluOjE3MTQ1NTg5NTE= ok";
+        assert_eq!(ResponseAnalyzer::normalize(token), format!("session {} ok", DYNAMIC));
+    }
+
+    #[test]
+    fn test_compare_reflection_and_headers() {
+        let mut baseline = snapshot(200, "<p>Results for shoes</p>");
+        baseline.headers.insert("date".to_string(), "Mon".to_string());
+        let mut response = snapshot(200, "<p>Results for shoes&#39; AND 1=1</p>");
+        response.headers.insert("date".to_string(), "Tue".to_string());
+        response.headers.insert("x-debug".to_string(), "1".to_string());
+
+        let baseline = Baseline::from_samples(&[baseline]);
+        let comparison = ResponseAnalyzer::compare(&baseline, &response, Some("' AND 1=1"));
+        assert!(comparison.reflected);
+        assert!(!comparison.status_changed);
+        assert_eq!(comparison.changed_headers, vec!["x-debug"]);
+        assert_eq!(comparison.similarity, 1.0);
+        assert_eq!(comparison.length_delta, 0);
+        assert!(!comparison.length_anomalous);
+
+        assert!(comparison.new_errors.is_empty());
+    }
+
+    #[test]
+    fn test_reflection_contexts() {
+        let canary = "rlx0123456789z";
+        let page = format!(
+            "<p title=\"a>b {c}\">{c}</p><input value='{c}'><img alt={c}><!-- <b {c} --><script>var q = \"{c}\";</script>",
+            c = canary
+        );
+        let mut response = snapshot(200, &page);
+        response.headers.insert("location".to_string(), format!("/search?q={}", canary));
+
+        let contexts = ResponseAnalyzer::reflection_contexts(canary, &response);
+        assert_eq!(
+            contexts,
+            vec![
+                ReflectionContext::Header,
+                ReflectionContext::DoubleQuotedAttribute,
+                ReflectionContext::HtmlText,
+                ReflectionContext::SingleQuotedAttribute,
+                ReflectionContext::UnquotedAttribute,
+                ReflectionContext::HtmlText,
+                ReflectionContext::Script,
+            ]
+        );
+        assert_eq!(
+            ResponseAnalyzer::describe_reflections(&contexts[..3]),
+            "response header, double-quoted attribute, HTML text"
+        );
+
+        let json = snapshot(200, &format!("{{\"results\":[],\"query\":\"{}\"}}", canary));
+        assert_eq!(ResponseAnalyzer::reflection_contexts(canary, &json), vec![ReflectionContext::JsonString]);
+        let mut json = snapshot(200, &format!("{{\"note\":\"a \\\"b\\\"\",\"id\":7{}}}", canary));
+        json.headers.insert("content-type".to_string(), "application/json".to_string());
+        assert_eq!(ResponseAnalyzer::reflection_contexts(canary, &json), vec![ReflectionContext::JsonLiteral]);
+        let absent = ResponseAnalyzer::reflection_contexts(canary, &snapshot(200, "ok"));
+        assert_eq!(ResponseAnalyzer::describe_reflections(&absent), "not reflected");
+    }
+
+    #[test]
+    fn test_baseline_learns_noise() {
+        let page = |visitors: u32, ad: &str| {
+            format!("<h1>Shop</h1> <p>Red shoes in stock</p> <p>{} visitors online</p> <aside>{}</aside>", visitors, ad)
+        };
+        let mut samples: Vec<ResponseSnapshot> = [(17, "Buy pies"), (23, "Cheap flights today"), (19, "Buy pies")]
+            .iter()
+            .map(|(visitors, ad)| snapshot(200, &page(*visitors, ad)))
+            .collect();
+        for (sample, ms) in samples.iter_mut().zip([100, 120, 110]) {
+            sample.elapsed = Duration::from_millis(ms);
+        }
+        let baseline = Baseline::from_samples(&samples);
+
+        // Another load with different dynamic values is still the same page
+        let same = snapshot(200, &page(31, "New cars"));
+        assert_eq!(baseline.similarity(&same.body), 1.0);
+        assert!(baseline.similarity("<h1>Shop</h1> <p>No results</p>") < 0.7);
+
+        assert!(baseline.length_is_noise(baseline.length_mean.round() as usize));
+        assert!(!baseline.length_is_noise(10));
+        assert!(baseline.time_is_noise(0.3));
+        assert!(!baseline.time_is_noise(2.2));
+    }
+}
@@ -0,0 +1,271 @@
+// src/modules/auxiliary/crawler.rs
+
+// Web crawler
+
+use crate::core::http_request::HttpRequest;
+use crate::core::request_engine::RequestEngine;
+use crate::core::safety::RiskLevel;
+use crate::core::scope::Scope;
+use crate::core::site_map::{endpoint_key, is_skipped_resource, Endpoint, EndpointSource, SiteMap};
+use crate::modules::auxiliary::discovery::{derive, ParameterDiscovery};
+use std::collections::{HashSet, VecDeque};
+use url::Url;
+
+/// Most sitemap files read while seeding, including nested ones from sitemap indexes
+const MAX_SITEMAPS: usize = 10;
+
+/// Limits of a crawl
+#[derive(Debug, Clone, Copy)]
+pub struct CrawlerConfig {
+    /// Links followed from the start URL; robots.txt and sitemap entries count as one
+    pub max_depth: usize,
+    /// Pages requested, not counting robots.txt and sitemaps
+    pub max_pages: usize,
+}
+
+/// Default implementation for CrawlerConfig
+impl Default for CrawlerConfig {
+    fn default() -> Self {
+        Self { max_depth: 3, max_pages: 100 }
+    }
+}
+
+/// Builds a site map by following links breadth-first from a start URL,
+/// staying within the active scope
+pub struct Crawler {
+    request_engine: RequestEngine,
+    config: CrawlerConfig,
+}
+
+impl Crawler {
+    pub fn new(config: CrawlerConfig) -> Self {
+        Self { request_engine: RequestEngine::new(), config }
+    }
+
+    /// Crawls from `start`, whose headers (cookies, authorization) are sent
+    /// with every request
+    pub async fn crawl(&self, start: &HttpRequest) -> Result<SiteMap, Box<dyn std::error::Error + Send + Sync>> {
+        Scope::check_active(&start.url)?;
+        let base = Url::parse(&start.url)?;
+        log::info!(
+            "Crawling {} (depth {}, up to {} pages)",
+            start.url,
+            self.config.max_depth,
+            self.config.max_pages
+        );
+
+        let mut site_map = SiteMap::new(&start.url);
+        let mut queue: VecDeque<(HttpRequest, EndpointSource, usize)> = VecDeque::new();
+        queue.push_back((start.clone(), EndpointSource::Target, 0));
+        if self.config.max_depth > 0 {
+            for (url, source) in self.seeds(&base).await {
+                queue.push_back((derive(start, "GET", url.as_str()), source, 1));
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut pages = 0;
+        while let Some((request, source, depth)) = queue.pop_front() {
+            if is_skipped_resource(&request.url) || !visited.insert(endpoint_key(&request)) {
+                continue;
+            }
+            if pages >= self.config.max_pages {
+                log::info!("Page limit of {} reached; {} URLs left unvisited", self.config.max_pages, queue.len() + 1);
+                break;
+            }
+            pages += 1;
+
+            let response = match self.request_engine.send(&request, "crawl", RiskLevel::ReadOnly).await {
+                Ok(response) => response,
+                Err(e) => {
+                    log::debug!("Failed to crawl {}: {}", request.url, e);
+                    continue;
+                }
+            };
+            let status = response.status().as_u16();
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string());
+            let body = response.text().await.unwrap_or_default();
+
+            let mut endpoint = Endpoint::new(request.clone(), source, depth);
+            endpoint.status = Some(status);
+            endpoint.content_type = content_type.clone();
+            site_map.add(endpoint);
+
+            let is_html = match &content_type {
+                Some(content_type) => content_type.contains("html"),
+                None => body.trim_start().starts_with('<'),
+            };
+            if !is_html || status >= 400 {
+                continue;
+            }
+
+            let Ok(page) = Url::parse(&request.url) else {
+                continue;
+            };
+            let links = ParameterDiscovery::links(&page, &body);
+            let discovered = ParameterDiscovery::analyze(&request, &body, content_type.as_deref());
+
+            // Forms that send a body may change state, so the crawl records them without
+            // submitting them; scans replay them only when unsafe methods are enabled
+            for form in discovered.requests.iter().skip(1).filter(|r| r.body.is_some()) {
+                site_map.add(Endpoint::new(form.clone(), EndpointSource::Form, depth + 1));
+            }
+            if depth >= self.config.max_depth {
+                continue;
+            }
+            for next in discovered.requests.iter().skip(1).filter(|r| r.body.is_none()) {
+                let source = if links.iter().any(|link| link.as_str() == next.url) {
+                    EndpointSource::Link
+                } else {
+                    EndpointSource::Form
+                };
+                queue.push_back((next.clone(), source, depth + 1));
+            }
+            for link in links {
+                queue.push_back((derive(start, "GET", link.as_str()), EndpointSource::Link, depth + 1));
+            }
+        }
+
+        log::info!(
+            "Crawl of {} requested {} pages and found {} endpoints",
+            start.url,
+            pages,
+            site_map.endpoints.len()
+        );
+        Ok(site_map)
+    }
+
+    /// Start points from robots.txt (its paths and sitemaps) and `/sitemap.xml`
+    async fn seeds(&self, base: &Url) -> Vec<(Url, EndpointSource)> {
+        let mut seeds = Vec::new();
+        let mut sitemaps: VecDeque<Url> = base.join("/sitemap.xml").into_iter().collect();
+
+        if let Some(robots) = self.fetch_text(base, "/robots.txt").await {
+            let (paths, robots_sitemaps) = parse_robots(&robots);
+            // Disallowed paths are often the interesting ones; scope decides what is crawled
+            seeds.extend(paths.iter().filter_map(|path| base.join(path).ok()).map(|url| (url, EndpointSource::Robots)));
+            sitemaps.extend(robots_sitemaps.iter().filter_map(|url| base.join(url).ok()));
+        }
+
+        let mut read = Vec::new();
+        while let Some(sitemap) = sitemaps.pop_front() {
+            if read.len() >= MAX_SITEMAPS {
+                break;
+            }
+            if read.contains(&sitemap) {
+                continue;
+            }
+            read.push(sitemap.clone());
+
+            let Some(xml) = self.fetch_text(&sitemap, "").await else {
+                continue;
+            };
+            let (locations, is_index) = parse_sitemap(&xml);
+            let urls = locations.iter().filter_map(|location| base.join(location).ok());
+            if is_index {
+                sitemaps.extend(urls);
+            } else {
+                seeds.extend(urls.map(|url| (url, EndpointSource::Sitemap)));
+            }
+        }
+
+        seeds.retain(|(url, _)| url.origin() == base.origin() && Scope::check_active(url.as_str()).is_ok());
+        log::info!("Seeded the crawl with {} URLs from robots.txt and sitemaps", seeds.len());
+        seeds
+    }
+
+    /// Body of a successful GET of `path` resolved against `base`
+    async fn fetch_text(&self, base: &Url, path: &str) -> Option<String> {
+        let url = base.join(path).ok()?;
+        Scope::check_active(url.as_str()).ok()?;
+        let response = self
+            .request_engine
+            .send(&HttpRequest::get(url.as_str()), "crawl", RiskLevel::ReadOnly)
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.text().await.ok()
+    }
+}
+
+/// Default implementation for Crawler
+impl Default for Crawler {
+    fn default() -> Self {
+        Self::new(CrawlerConfig::default())
+    }
+}
+
+/// Paths (from `Allow` and `Disallow`) and sitemap URLs listed in robots.txt;
+/// wildcard rules are not paths and are skipped
+fn parse_robots(robots: &str) -> (Vec<String>, Vec<String>) {
+    let mut paths = Vec::new();
+    let mut sitemaps = Vec::new();
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match field.trim().to_ascii_lowercase().as_str() {
+            "allow" | "disallow"
+                if value.starts_with('/')
+                    && value.len() > 1
+                    && !value.contains(['*', '$'])
+                    && !paths.iter().any(|p| p == value) =>
+            {
+                paths.push(value.to_string());
+            }
+            "sitemap" if !value.is_empty() => sitemaps.push(value.to_string()),
+            _ => {}
+        }
+    }
+    (paths, sitemaps)
+}
+
+/// `<loc>` entries of a sitemap and whether it is a sitemap index
+fn parse_sitemap(xml: &str) -> (Vec<String>, bool) {
+    let Ok(document) = roxmltree::Document::parse(xml) else {
+        return (Vec::new(), false);
+    };
+    let locations = document
+        .descendants()
+        .filter(|node| node.tag_name().name() == "loc")
+        .filter_map(|node| node.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect();
+    (locations, document.root_element().tag_name().name() == "sitemapindex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_robots() {
+        let robots = "User-agent: *\nDisallow: /admin/ # staff only\nDisallow: /*.php$\nAllow: /public\nDisallow: /\n\
+                      Sitemap: https://example.com/sitemap_index.xml\n";
+        let (paths, sitemaps) = parse_robots(robots);
+        assert_eq!(paths, vec!["/admin/", "/public"]);
+        assert_eq!(sitemaps, vec!["https://example.com/sitemap_index.xml"]);
+    }
+
+    #[test]
+    fn test_parse_sitemap() {
+        let index = r#"<?xml version="1.0"?><sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <sitemap><loc>https://example.com/sitemap-posts.xml</loc></sitemap></sitemapindex>"#;
+        assert_eq!(parse_sitemap(index), (vec!["https://example.com/sitemap-posts.xml".to_string()], true));
+
+        let urlset = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+            <url><loc> https://example.com/post?id=1 </loc></url><url><loc>https://example.com/about</loc></url></urlset>"#;
+        let (locations, is_index) = parse_sitemap(urlset);
+        assert_eq!(locations, vec!["https://example.com/post?id=1", "https://example.com/about"]);
+        assert!(!is_index);
+    }
+}
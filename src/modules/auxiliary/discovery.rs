@@ -1,3 +1,487 @@
 // src/modules/auxiliary/discovery.rs
 
 // Parameter discovery
+
+use crate::core::http_request::HttpRequest;
+use crate::core::injection::{InjectionLocation, InjectionPoint};
+use crate::core::request_engine::RequestEngine;
+use crate::core::safety::{RiskLevel, Safety};
+use crate::core::scope::Scope;
+use crate::core::site_map::{endpoint_key, Endpoint};
+use crate::modules::auxiliary::param_miner::ParameterMiner;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::LazyLock;
+use url::{form_urlencoded, Url};
+
+/// Most requests (target, forms and links) returned by one discovery run
+const MAX_REQUESTS: usize = 25;
+
+/// Input types that never carry user-controlled data
+const SKIPPED_INPUT_TYPES: &[&str] = &["submit", "button", "image", "reset", "file"];
+
+/// Parameter names in inline JavaScript: query strings in URLs, URLSearchParams
+/// and FormData calls
+static JS_PARAMETER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?x)
+        [?&]([A-Za-z_][\w.\[\]-]*)=
+        | (?:searchParams|URLSearchParams\([^)]*\)|params|formData|data)\.(?:get|set|append|has)\(\s*["']([\w.\[\]-]+)["']"#,
+    )
+    .expect("JavaScript parameter pattern is valid")
+});
+
+/// Where a discovered parameter was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParameterSource {
+    QueryString,
+    Form,
+    Link,
+    JavaScript,
+    Json,
+    /// Not advertised anywhere, but changed the response when sent
+    Mined,
+}
+
+impl fmt::Display for ParameterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParameterSource::QueryString => "query string",
+            ParameterSource::Form => "HTML form",
+            ParameterSource::Link => "link",
+            ParameterSource::JavaScript => "inline JavaScript",
+            ParameterSource::Json => "JSON response",
+            ParameterSource::Mined => "wordlist mining",
+        })
+    }
+}
+
+/// A parameter the application is known to accept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredParameter {
+    pub name: String,
+    /// Value seen in the application, used as the original value when injecting
+    pub value: String,
+    pub source: ParameterSource,
+}
+
+/// Requests and parameters found on a target page
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryResult {
+    /// Requests worth testing: the target itself, each form submission and each
+    /// distinct in-scope link with a query string
+    pub requests: Vec<HttpRequest>,
+    /// Every parameter found, with where it came from
+    pub parameters: Vec<DiscoveredParameter>,
+}
+
+/// A request an injection module tests and the injection points to test in it
+#[derive(Debug, Clone)]
+pub struct InjectionTarget {
+    pub request: HttpRequest,
+    pub points: Vec<InjectionPoint>,
+}
+
+impl InjectionTarget {
+    /// An endpoint of a site map or imported API with the points its source
+    /// declared, which replace discovered ones at the same place. The endpoint
+    /// was found when the site map was built, so nothing is sent to discover more.
+    pub fn from_endpoint(endpoint: &Endpoint) -> Self {
+        let mut points = InjectionPoint::discover(&endpoint.request);
+        for point in &endpoint.injection_points {
+            points.retain(|p| !p.same_target(point));
+            points.push(point.clone());
+        }
+        Self { request: endpoint.request.clone(), points }
+    }
+
+    /// Returns true if the request may be sent under the safety policy; a
+    /// submitted form or any other request that may change data is only
+    /// replayed when unsafe methods are enabled
+    pub fn allowed(&self) -> bool {
+        Safety::allows_request(self.request.risk())
+    }
+}
+
+impl DiscoveryResult {
+    /// What an injection module should test: the target first, then each derived
+    /// request. Headers and cookies are shared by all of them, so they are only
+    /// tested on the target, and names only seen in scripts, JSON data or by
+    /// mining are tried as query parameters of the target.
+    pub fn injection_targets(&self) -> Vec<InjectionTarget> {
+        let mut targets: Vec<InjectionTarget> = self
+            .requests
+            .iter()
+            .enumerate()
+            .map(|(index, request)| {
+                let mut points = InjectionPoint::discover(request);
+                if index > 0 {
+                    points.retain(|p| !matches!(p.location, InjectionLocation::Header | InjectionLocation::Cookie));
+                }
+                InjectionTarget { request: request.clone(), points }
+            })
+            .collect();
+
+        if let Some(target) = targets.first_mut() {
+            for candidate in self.query_candidates() {
+                let known = target.points.iter().any(|p| p.location == InjectionLocation::Query && p.name == candidate.name);
+                if !known {
+                    target.points.push(InjectionPoint::query(&candidate.name, &candidate.value));
+                }
+            }
+        }
+        targets
+    }
+
+    /// Parameters seen only in scripts or JSON data, or found by mining, with no
+    /// request carrying them yet; they are tested as query parameters of the target
+    pub fn query_candidates(&self) -> impl Iterator<Item = &DiscoveredParameter> {
+        self.parameters.iter().filter(|p| {
+            matches!(p.source, ParameterSource::JavaScript | ParameterSource::Json | ParameterSource::Mined)
+        })
+    }
+
+    fn add_parameter(&mut self, name: &str, value: &str, source: ParameterSource) {
+        if !self.parameters.iter().any(|p| p.name == name && p.source == source) {
+            self.parameters.push(DiscoveredParameter {
+                name: name.to_string(),
+                value: value.to_string(),
+                source,
+            });
+        }
+    }
+
+    /// Adds a request unless a near-identical one exists
+    fn add_request(&mut self, request: HttpRequest, seen: &mut HashSet<String>) {
+        if self.requests.len() >= MAX_REQUESTS {
+            log::debug!("Discovery request limit reached; skipping {} {}", request.method, request.url);
+            return;
+        }
+        if seen.insert(endpoint_key(&request)) {
+            self.requests.push(request);
+        }
+    }
+}
+
+/// Finds the parameters a page accepts from its URL, forms, links, inline
+/// scripts and JSON data, then mines it for hidden ones
+pub struct ParameterDiscovery;
+
+impl ParameterDiscovery {
+    /// Loads the target, extracts everything it exposes and mines the names
+    /// of the wordlist it does not
+    pub async fn discover(
+        engine: &RequestEngine,
+        request: &HttpRequest,
+    ) -> Result<DiscoveryResult, Box<dyn std::error::Error + Send + Sync>> {
+        // GraphQL takes its input as typed arguments, not as page parameters
+        if request.is_graphql() {
+            return Ok(DiscoveryResult { requests: vec![request.clone()], ..DiscoveryResult::default() });
+        }
+        let response = engine.send(request, "discovery", RiskLevel::ReadOnly).await?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let body = response.text().await.unwrap_or_default();
+
+        let mut result = Self::analyze(request, &body, content_type.as_deref());
+
+        let known: HashSet<String> = result.parameters.iter().map(|p| p.name.clone()).collect();
+        match ParameterMiner::new().mine(engine, request, &known).await {
+            Ok(mined) => result.parameters.extend(mined),
+            Err(e) => log::warn!("Parameter mining failed on {}: {}", request.url, e),
+        }
+
+        log::info!(
+            "Discovered {} parameters and {} requests to test on {}",
+            result.parameters.len(),
+            result.requests.len(),
+            request.url
+        );
+        Ok(result)
+    }
+
+    /// Runs discovery on `request` and returns what an injection module should
+    /// test, falling back to the request as given when discovery fails or the
+    /// request may not be sent
+    pub async fn injection_targets(engine: &RequestEngine, request: &HttpRequest) -> Vec<InjectionTarget> {
+        let fallback = || DiscoveryResult { requests: vec![request.clone()], ..DiscoveryResult::default() };
+        let discovered = if !Safety::allows_request(request.risk()) {
+            fallback()
+        } else {
+            match Self::discover(engine, request).await {
+                Ok(discovered) => discovered,
+                Err(e) => {
+                    log::warn!("Parameter discovery failed, testing the request as given: {}", e);
+                    fallback()
+                }
+            }
+        };
+        discovered.injection_targets()
+    }
+
+    /// Same-origin, in-scope URLs an HTML page links to, in document order
+    pub fn links(base: &Url, body: &str) -> Vec<Url> {
+        linked_urls(&Html::parse_document(body), base)
+    }
+
+    /// Extracts parameters and requests from a response to `request`
+    pub fn analyze(request: &HttpRequest, body: &str, content_type: Option<&str>) -> DiscoveryResult {
+        let mut result = DiscoveryResult::default();
+        let mut seen = HashSet::new();
+        result.add_request(request.clone(), &mut seen);
+
+        let Ok(base) = Url::parse(&request.url) else {
+            return result;
+        };
+        for (name, value) in base.query_pairs() {
+            result.add_parameter(&name, &value, ParameterSource::QueryString);
+        }
+
+        let is_json = content_type.is_some_and(|ct| ct.contains("json"))
+            || (content_type.is_none() && matches!(body.trim_start().chars().next(), Some('{') | Some('[')));
+        if is_json {
+            if let Ok(json) = serde_json::from_str::<JsonValue>(body) {
+                collect_json_keys(&json, &mut result);
+            }
+            return result;
+        }
+
+        let document = Html::parse_document(body);
+        collect_forms(&document, request, &base, &mut result, &mut seen);
+        collect_links(&document, request, &base, &mut result, &mut seen);
+        collect_scripts(&document, &mut result);
+
+        result
+    }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("selector is valid")
+}
+
+fn collect_forms(
+    document: &Html,
+    original: &HttpRequest,
+    base: &Url,
+    result: &mut DiscoveryResult,
+    seen: &mut HashSet<String>,
+) {
+    let fields = selector("input[name], select[name], textarea[name]");
+    let option = selector("option");
+
+    for form in document.select(&selector("form")) {
+        let action = form.value().attr("action").unwrap_or("");
+        let Some(action) = resolve(base, action) else {
+            continue;
+        };
+        let method = form.value().attr("method").unwrap_or("GET").to_uppercase();
+
+        let mut pairs = Vec::new();
+        for field in form.select(&fields) {
+            let element = field.value();
+            let name = element.attr("name").unwrap_or_default();
+            let input_type = element.attr("type").unwrap_or("text").to_ascii_lowercase();
+            if name.is_empty() || SKIPPED_INPUT_TYPES.contains(&input_type.as_str()) {
+                continue;
+            }
+
+            let value = match element.name() {
+                "select" => selected_option(&field, &option),
+                "textarea" => field.text().collect(),
+                _ => element.attr("value").unwrap_or_default().to_string(),
+            };
+            result.add_parameter(name, &value, ParameterSource::Form);
+            pairs.push((name.to_string(), value));
+        }
+        if pairs.is_empty() {
+            continue;
+        }
+
+        let encoded = form_urlencoded::Serializer::new(String::new()).extend_pairs(&pairs).finish();
+        let request = if method == "POST" {
+            let mut request = derive(original, "POST", action.as_str());
+            request.set_header("Content-Type", "application/x-www-form-urlencoded");
+            request.body = Some(encoded);
+            request
+        } else {
+            let mut url = action.clone();
+            url.set_query(Some(&encoded));
+            derive(original, "GET", url.as_str())
+        };
+        result.add_request(request, seen);
+    }
+}
+
+fn selected_option(select: &ElementRef, option: &Selector) -> String {
+    let options: Vec<ElementRef> = select.select(option).collect();
+    options
+        .iter()
+        .find(|o| o.value().attr("selected").is_some())
+        .or(options.first())
+        .map(|o| o.value().attr("value").map(|v| v.to_string()).unwrap_or_else(|| o.text().collect()))
+        .unwrap_or_default()
+}
+
+fn collect_links(
+    document: &Html,
+    original: &HttpRequest,
+    base: &Url,
+    result: &mut DiscoveryResult,
+    seen: &mut HashSet<String>,
+) {
+    for url in linked_urls(document, base) {
+        if url.query().is_none_or(|q| q.is_empty()) {
+            continue;
+        }
+
+        for (name, value) in url.query_pairs() {
+            result.add_parameter(&name, &value, ParameterSource::Link);
+        }
+        result.add_request(derive(original, "GET", url.as_str()), seen);
+    }
+}
+
+/// Same-origin, in-scope targets of the page's links and frames
+fn linked_urls(document: &Html, base: &Url) -> Vec<Url> {
+    document
+        .select(&selector("a[href], area[href], iframe[src]"))
+        .filter_map(|link| resolve(base, link.value().attr("href").or(link.value().attr("src")).unwrap_or_default()))
+        .collect()
+}
+
+fn collect_scripts(document: &Html, result: &mut DiscoveryResult) {
+    for script in document.select(&selector("script:not([src])")) {
+        let code: String = script.text().collect();
+        for captures in JS_PARAMETER.captures_iter(&code) {
+            if let Some(name) = captures.get(1).or(captures.get(2)) {
+                result.add_parameter(name.as_str(), "1", ParameterSource::JavaScript);
+            }
+        }
+    }
+}
+
+/// Keys of scalar values anywhere in a JSON document
+fn collect_json_keys(value: &JsonValue, result: &mut DiscoveryResult) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, child) in map {
+                match child {
+                    JsonValue::Object(_) | JsonValue::Array(_) => collect_json_keys(child, result),
+                    JsonValue::Null => result.add_parameter(key, "1", ParameterSource::Json),
+                    JsonValue::String(s) => result.add_parameter(key, s, ParameterSource::Json),
+                    scalar => result.add_parameter(key, &scalar.to_string(), ParameterSource::Json),
+                }
+            }
+        }
+        JsonValue::Array(items) => items.iter().for_each(|item| collect_json_keys(item, result)),
+        _ => {}
+    }
+}
+
+/// A new request that keeps the original's headers, such as cookies and
+/// authorization, but none of its body
+pub(crate) fn derive(original: &HttpRequest, method: &str, url: &str) -> HttpRequest {
+    let mut request = HttpRequest::get(url);
+    request.method = method.to_string();
+    request.headers = original
+        .headers
+        .iter()
+        .filter(|(name, _)| !name.eq_ignore_ascii_case("content-type") && !name.eq_ignore_ascii_case("content-length"))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    request
+}
+
+/// Resolves a link against the page URL, keeping only same-origin, in-scope HTTP URLs
+fn resolve(base: &Url, href: &str) -> Option<Url> {
+    let href = href.trim();
+    if href.starts_with('#') || href.starts_with("javascript:") || href.starts_with("mailto:") {
+        return None;
+    }
+
+    let mut url = base.join(href).ok()?;
+    url.set_fragment(None);
+    if url.origin() != base.origin() || Scope::check_active(url.as_str()).is_err() {
+        return None;
+    }
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"
+        <html><body>
+          <form action="/login" method="post">
+            <input type="text" name="username">
+            <input type="password" name="password">
+            <input type="hidden" name="csrf" value="abc123">
+            <select name="lang"><option value="en">English</option><option value="de" selected>Deutsch</option></select>
+            <input type="submit" name="go" value="Log in">
+          </form>
+          <form><input name="q" value="shoes"></form>
+          <a href="/product?id=7&ref=home">Product 7</a>
+          <a href="/product?id=8&ref=home">Product 8</a>
+          <a href="https://other.example.org/?id=1">Elsewhere</a>
+          <a href="/about">About</a>
+          <script>
+            fetch('/api/items?category=' + cat + '&sort=price');
+            const debug = new URLSearchParams(location.search).get('debug');
+          </script>
+        </body></html>"#;
+
+    fn names(result: &DiscoveryResult, source: ParameterSource) -> Vec<&str> {
+        result.parameters.iter().filter(|p| p.source == source).map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_html_forms_links_and_scripts() {
+        let mut request = HttpRequest::get("http://shop.example.com/home?tab=new");
+        request.set_header("Cookie", "session=s1");
+        let result = ParameterDiscovery::analyze(&request, PAGE, Some("text/html"));
+
+        assert_eq!(names(&result, ParameterSource::QueryString), vec!["tab"]);
+        assert_eq!(names(&result, ParameterSource::Form), vec!["username", "password", "csrf", "lang", "q"]);
+        assert_eq!(names(&result, ParameterSource::Link), vec!["id", "ref"]);
+        assert_eq!(names(&result, ParameterSource::JavaScript), vec!["category", "sort", "debug"]);
+
+        let urls: Vec<String> = result.requests.iter().map(|r| format!("{} {}", r.method, r.url)).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "GET http://shop.example.com/home?tab=new",
+                "POST http://shop.example.com/login",
+                "GET http://shop.example.com/home?q=shoes",
+                "GET http://shop.example.com/product?id=7&ref=home",
+            ]
+        );
+        assert_eq!(result.requests[1].body.as_deref(), Some("username=&password=&csrf=abc123&lang=de"));
+        assert!(result.requests.iter().all(|r| r.header("cookie") == Some("session=s1")));
+
+        // The POST form may change data; cookies are only tested on the target
+        let targets = result.injection_targets();
+        let risks: Vec<RiskLevel> = targets.iter().map(|t| t.request.risk()).collect();
+        assert_eq!(risks[..2], [RiskLevel::ReadOnly, RiskLevel::StateChanging]);
+        assert!(targets[0].points.iter().any(|p| p.location == InjectionLocation::Cookie));
+        assert!(targets[1..].iter().all(|t| t.points.iter().all(|p| p.location != InjectionLocation::Cookie)));
+        assert!(targets[0].points.iter().any(|p| p.name == "debug"));
+    }
+
+    #[test]
+    fn test_json_keys() {
+        let request = HttpRequest::get("http://api.example.com/v1/users");
+        let body = r#"{"items":[{"id":3,"name":"bob","address":{"city":"Oslo"}}],"page":1,"next":null}"#;
+        let result = ParameterDiscovery::analyze(&request, body, Some("application/json"));
+
+        assert_eq!(names(&result, ParameterSource::Json), vec!["city", "id", "name", "next", "page"]);
+        assert_eq!(result.query_candidates().count(), 5);
+        assert_eq!(result.requests.len(), 1);
+    }
+}
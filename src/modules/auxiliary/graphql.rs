@@ -0,0 +1,536 @@
+// src/modules/auxiliary/graphql.rs
+
+// GraphQL endpoint detection, introspection and query generation
+
+use crate::core::http_request::HttpRequest;
+use crate::core::logger::Logger;
+use crate::core::modules::{ScanResult, SecurityModule};
+use crate::core::request_engine::RequestEngine;
+use crate::core::safety::RiskLevel;
+use crate::core::site_map::{Endpoint, EndpointSource, SiteMap};
+use crate::core::vulnerability::{SeverityLevel, Vulnerability};
+use async_trait::async_trait;
+use serde_json::{json, Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use url::Url;
+
+/// Paths tried, relative to the target's origin, when the target itself is not
+/// a GraphQL endpoint
+const COMMON_PATHS: &[&str] = &[
+    "/graphql", "/api/graphql", "/graphql/v1", "/v1/graphql", "/api/v1/graphql", "/gql", "/query", "/graphql/api",
+];
+
+/// Query every GraphQL server answers, used to recognise one
+const TYPENAME_QUERY: &str = "query { __typename }";
+
+/// Introspection query for the types, fields and arguments needed to build queries
+const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { \
+    queryType { name } mutationType { name } \
+    types { kind name \
+      fields(includeDeprecated: true) { name args { name defaultValue type { ...TypeRef } } type { ...TypeRef } } \
+      inputFields { name defaultValue type { ...TypeRef } } \
+      enumValues(includeDeprecated: true) { name } } } } \
+    fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name ofType { kind name \
+      ofType { kind name ofType { kind name ofType { kind name } } } } } } }";
+
+/// Levels of nested objects selected below each generated root field
+const MAX_SELECTION_DEPTH: usize = 2;
+
+/// Fields selected per object, so queries on wide types stay small
+const MAX_SELECTION_FIELDS: usize = 12;
+
+/// Levels of nested input objects filled in for an argument
+const MAX_INPUT_DEPTH: usize = 3;
+
+/// Names of the root operations listed in the introspection finding
+const MAX_LISTED_OPERATIONS: usize = 20;
+
+const REMEDIATION: &str = "Disable introspection in production, or restrict it to authenticated \
+    developers. Keep authorization checks on every resolver, since a hidden schema can still be guessed.";
+
+/// A GraphQL schema, kept as the introspection result
+#[derive(Debug, Clone)]
+pub struct GraphQlSchema {
+    query_type: Option<String>,
+    mutation_type: Option<String>,
+    types: HashMap<String, JsonValue>,
+}
+
+impl GraphQlSchema {
+    /// Reads the `__schema` of an introspection response
+    pub fn from_introspection(response: &JsonValue) -> Option<Self> {
+        let schema = response.pointer("/data/__schema")?;
+        let name = |pointer: &str| schema.pointer(pointer).and_then(JsonValue::as_str).map(str::to_string);
+        let types = schema
+            .get("types")?
+            .as_array()?
+            .iter()
+            .filter_map(|t| Some((t.get("name")?.as_str()?.to_string(), t.clone())))
+            .collect();
+        Some(Self { query_type: name("/queryType/name"), mutation_type: name("/mutationType/name"), types })
+    }
+
+    /// Names of the root query fields
+    pub fn queries(&self) -> Vec<String> {
+        self.root_fields(self.query_type.as_deref())
+    }
+
+    /// Names of the root mutation fields
+    pub fn mutations(&self) -> Vec<String> {
+        self.root_fields(self.mutation_type.as_deref())
+    }
+
+    /// Builds one request per root field of the query type (and the mutation
+    /// type if `include_mutations`), with every argument passed as a variable
+    pub fn operations(&self, endpoint: &HttpRequest, include_mutations: bool) -> Vec<Endpoint> {
+        let mut roots = vec![("query", self.query_type.as_deref())];
+        if include_mutations {
+            roots.push(("mutation", self.mutation_type.as_deref()));
+        }
+
+        let mut endpoints = Vec::new();
+        for (keyword, root) in roots {
+            let Some(root) = root.and_then(|name| self.types.get(name)) else {
+                continue;
+            };
+            for field in fields(root).iter().filter(|f| !field_name(f).starts_with("__")) {
+                let mut request = endpoint.clone();
+                request.method = "POST".to_string();
+                request.set_header("Content-Type", "application/json");
+                request.body = Some(self.operation(keyword, field).to_string());
+                endpoints.push(Endpoint::new(request, EndpointSource::GraphQl, 0));
+            }
+        }
+        endpoints
+    }
+
+    /// A request document for one root field: `query name($a: T) { name(a: $a) { ... } }`
+    fn operation(&self, keyword: &str, field: &JsonValue) -> JsonValue {
+        let name = field_name(field);
+        let arguments = field.get("args").and_then(JsonValue::as_array).cloned().unwrap_or_default();
+
+        let mut declarations = Vec::new();
+        let mut uses = Vec::new();
+        let mut variables = Map::new();
+        for argument in &arguments {
+            let Some(type_ref) = argument.get("type") else {
+                continue;
+            };
+            let argument_name = field_name(argument);
+            declarations.push(format!("${}: {}", argument_name, type_string(type_ref)));
+            uses.push(format!("{0}: ${0}", argument_name));
+            variables.insert(argument_name.to_string(), self.example(type_ref, 0));
+        }
+
+        let mut query = format!("{} {}", keyword, name);
+        if !declarations.is_empty() {
+            query.push_str(&format!("({})", declarations.join(", ")));
+        }
+        query.push_str(&format!(" {{ {}", name));
+        if !uses.is_empty() {
+            query.push_str(&format!("({})", uses.join(", ")));
+        }
+        if let Some(selection) = field.get("type").and_then(|t| self.selection(t, 0)) {
+            query.push_str(&format!(" {}", selection));
+        }
+        query.push_str(" }");
+
+        json!({ "query": query, "operationName": name, "variables": variables })
+    }
+
+    /// The selection set for a value of `type_ref`: scalar fields that need no
+    /// arguments, with nested objects down to `MAX_SELECTION_DEPTH`; `None` for
+    /// scalars and enums
+    fn selection(&self, type_ref: &JsonValue, depth: usize) -> Option<String> {
+        let named = self.types.get(named_type(type_ref)?)?;
+        match named.get("kind").and_then(JsonValue::as_str)? {
+            "OBJECT" | "INTERFACE" => {}
+            "UNION" => return Some("{ __typename }".to_string()),
+            _ => return None,
+        }
+
+        let mut selected = Vec::new();
+        for field in fields(named) {
+            if selected.len() >= MAX_SELECTION_FIELDS {
+                break;
+            }
+            let required_argument = field
+                .get("args")
+                .and_then(JsonValue::as_array)
+                .is_some_and(|args| args.iter().any(is_required));
+            let Some(field_type) = field.get("type").filter(|_| !required_argument) else {
+                continue;
+            };
+            if !self.is_composite(field_type) {
+                selected.push(field_name(field).to_string());
+            } else if depth < MAX_SELECTION_DEPTH
+                && let Some(nested) = self.selection(field_type, depth + 1)
+            {
+                selected.push(format!("{} {}", field_name(field), nested));
+            }
+        }
+        if selected.is_empty() {
+            selected.push("__typename".to_string());
+        }
+        Some(format!("{{ {} }}", selected.join(" ")))
+    }
+
+    /// Returns true for types that need a selection set: objects, interfaces and unions
+    fn is_composite(&self, type_ref: &JsonValue) -> bool {
+        named_type(type_ref)
+            .and_then(|name| self.types.get(name))
+            .and_then(|named| named.get("kind"))
+            .and_then(JsonValue::as_str)
+            .is_some_and(|kind| matches!(kind, "OBJECT" | "INTERFACE" | "UNION"))
+    }
+
+    /// An example value for an input type; custom scalars get values matching
+    /// common names (dates, URLs, emails, UUIDs)
+    fn example(&self, type_ref: &JsonValue, depth: usize) -> JsonValue {
+        match type_ref.get("kind").and_then(JsonValue::as_str) {
+            Some("NON_NULL") => return type_ref.get("ofType").map(|t| self.example(t, depth)).unwrap_or(JsonValue::Null),
+            Some("LIST") => {
+                return JsonValue::Array(type_ref.get("ofType").map(|t| vec![self.example(t, depth)]).unwrap_or_default());
+            }
+            _ => {}
+        }
+
+        let name = type_ref.get("name").and_then(JsonValue::as_str).unwrap_or_default();
+        let named = self.types.get(name);
+        match named.and_then(|t| t.get("kind")).and_then(JsonValue::as_str) {
+            Some("ENUM") => named
+                .and_then(|t| t.pointer("/enumValues/0/name"))
+                .cloned()
+                .unwrap_or(JsonValue::Null),
+            Some("INPUT_OBJECT") => {
+                let mut object = Map::new();
+                for field in named.and_then(|t| t.get("inputFields")).and_then(JsonValue::as_array).into_iter().flatten() {
+                    // Deeper levels only get the fields they cannot do without
+                    if depth >= MAX_INPUT_DEPTH && !is_required(field) {
+                        continue;
+                    }
+                    if let Some(field_type) = field.get("type") {
+                        object.insert(field_name(field).to_string(), self.example(field_type, depth + 1));
+                    }
+                }
+                JsonValue::Object(object)
+            }
+            _ => scalar_example(name),
+        }
+    }
+
+    fn root_fields(&self, root: Option<&str>) -> Vec<String> {
+        root.and_then(|name| self.types.get(name))
+            .map(|root| fields(root).iter().map(|f| field_name(f).to_string()).filter(|n| !n.starts_with("__")).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Detects GraphQL endpoints, reports enabled introspection and builds site
+/// maps of generated operations
+pub struct GraphQlScanner {
+    request_engine: Arc<RequestEngine>,
+}
+
+impl GraphQlScanner {
+    pub fn new() -> Self {
+        Self { request_engine: Arc::new(RequestEngine::new()) }
+    }
+
+    /// Finds the GraphQL endpoint at the target URL or one of the common
+    /// paths of its origin; returns a POST request to it carrying the target's
+    /// headers and cookies
+    pub async fn detect(&self, target: &HttpRequest) -> Option<HttpRequest> {
+        let url = Url::parse(&target.url).ok()?;
+        let mut candidates = Vec::new();
+        if url.path() != "/" {
+            candidates.push(url.clone());
+        }
+        candidates.extend(COMMON_PATHS.iter().filter_map(|path| url.join(path).ok()));
+
+        for candidate in candidates {
+            let mut endpoint = target.clone();
+            endpoint.method = "POST".to_string();
+            endpoint.url = candidate.to_string();
+            endpoint.set_header("Content-Type", "application/json");
+            endpoint.body = None;
+
+            let response = self.post(&endpoint, TYPENAME_QUERY, "graphql-detect").await;
+            if response.as_ref().and_then(|r| r.pointer("/data/__typename")).is_some_and(JsonValue::is_string) {
+                log::info!("Found GraphQL endpoint at {}", endpoint.url);
+                return Some(endpoint);
+            }
+        }
+        log::info!("No GraphQL endpoint found for {}", target.url);
+        None
+    }
+
+    /// Fetches the schema of an endpoint; `None` if introspection is disabled
+    pub async fn introspect(&self, endpoint: &HttpRequest) -> Option<GraphQlSchema> {
+        let response = self.post(endpoint, INTROSPECTION_QUERY, "graphql-introspection").await?;
+        GraphQlSchema::from_introspection(&response)
+    }
+
+    /// Detects the endpoint and introspects it, reporting enabled
+    /// introspection. Once an endpoint is found the site map holds one endpoint
+    /// per generated operation, or only the endpoint itself if introspection is
+    /// disabled.
+    pub async fn scan(&self, target: &HttpRequest, include_mutations: bool) -> (ScanResult, Option<SiteMap>) {
+        let start_time = std::time::Instant::now();
+        let mut result = ScanResult {
+            success: false,
+            vulnerabilities: Vec::new(),
+            warnings: Vec::new(),
+            duration: std::time::Duration::ZERO,
+        };
+
+        let Some(endpoint) = self.detect(target).await else {
+            result.warnings.push(format!("No GraphQL endpoint found for {}", target.url));
+            result.duration = start_time.elapsed();
+            return (result, None);
+        };
+
+        let mut site_map = SiteMap::new(&endpoint.url);
+        match self.introspect(&endpoint).await {
+            None => {
+                result.warnings.push(format!("Introspection is disabled on {}", endpoint.url));
+                let mut request = endpoint.clone();
+                request.body = Some(json!({ "query": TYPENAME_QUERY }).to_string());
+                site_map.add(Endpoint::new(request, EndpointSource::GraphQl, 0));
+            }
+            Some(schema) => {
+                Logger::vulnerability_found("GraphQL introspection enabled", &endpoint.url);
+                result.vulnerabilities.push(introspection_finding(&endpoint, &schema));
+                result.success = true;
+
+                for operation in schema.operations(&endpoint, include_mutations) {
+                    site_map.add(operation);
+                }
+                if !include_mutations && !schema.mutations().is_empty() {
+                    log::info!("Skipped {} mutations; they change data on the server", schema.mutations().len());
+                }
+                log::info!("Generated {} GraphQL operations for {}", site_map.endpoints.len(), endpoint.url);
+            }
+        }
+
+        result.duration = start_time.elapsed();
+        (result, Some(site_map))
+    }
+
+    /// Sends a query and returns the JSON response, if there is one
+    async fn post(&self, endpoint: &HttpRequest, query: &str, purpose: &str) -> Option<JsonValue> {
+        let mut request = endpoint.clone();
+        request.body = Some(json!({ "query": query }).to_string());
+        let response = match self.request_engine.send(&request, purpose, RiskLevel::ReadOnly).await {
+            Ok(response) => response,
+            Err(e) => {
+                log::debug!("GraphQL request to {} failed: {}", request.url, e);
+                return None;
+            }
+        };
+        response.json::<JsonValue>().await.ok()
+    }
+}
+
+/// Default implementation for GraphQlScanner
+impl Default for GraphQlScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SecurityModule for GraphQlScanner {
+    fn name(&self) -> &str {
+        "graphql"
+    }
+
+    fn description(&self) -> &str {
+        "GraphQL endpoint detection and introspection check"
+    }
+
+    fn author(&self) -> &str {
+        "Redlines X Security Team"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    async fn run(&self, target: &str) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_request(&HttpRequest::get(target)).await
+    }
+
+    /// Looks for a GraphQL endpoint and reports it if it answers introspection
+    async fn run_request(&self, request: &HttpRequest) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        let (result, _) = self.scan(request, false).await;
+        Ok(result)
+    }
+}
+
+fn introspection_finding(endpoint: &HttpRequest, schema: &GraphQlSchema) -> Vulnerability {
+    let (queries, mutations) = (schema.queries(), schema.mutations());
+    let list = |names: &[String]| {
+        let mut listed = names.iter().take(MAX_LISTED_OPERATIONS).cloned().collect::<Vec<_>>().join(", ");
+        if names.len() > MAX_LISTED_OPERATIONS {
+            listed.push_str(", ...");
+        }
+        listed
+    };
+    let mut request = endpoint.clone();
+    request.body = Some(json!({ "query": INTROSPECTION_QUERY }).to_string());
+
+    Vulnerability {
+        title: "GraphQL introspection enabled".to_string(),
+        description: format!(
+            "The GraphQL endpoint at {} answers introspection queries, exposing its whole schema \
+             ({} types, {} queries, {} mutations) including operations the client never uses.",
+            endpoint.url,
+            schema.types.len(),
+            queries.len(),
+            mutations.len()
+        ),
+        severity: SeverityLevel::Low,
+        evidence: format!("Endpoint: {}\nQueries: {}\nMutations: {}", endpoint.url, list(&queries), list(&mutations)),
+        request: Some(request.to_string()),
+        response: None,
+        remediation: REMEDIATION.to_string(),
+        dbms: None,
+        reflection: None,
+    }
+}
+
+fn fields(type_definition: &JsonValue) -> &[JsonValue] {
+    type_definition.get("fields").and_then(JsonValue::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+fn field_name(field: &JsonValue) -> &str {
+    field.get("name").and_then(JsonValue::as_str).unwrap_or_default()
+}
+
+/// Returns true for non-null arguments and input fields without a default
+fn is_required(argument: &JsonValue) -> bool {
+    argument.pointer("/type/kind").and_then(JsonValue::as_str) == Some("NON_NULL")
+        && argument.get("defaultValue").is_none_or(JsonValue::is_null)
+}
+
+/// The named type at the bottom of a list / non-null wrapper chain
+fn named_type(type_ref: &JsonValue) -> Option<&str> {
+    match type_ref.get("ofType") {
+        Some(inner) if !inner.is_null() => named_type(inner),
+        _ => type_ref.get("name").and_then(JsonValue::as_str),
+    }
+}
+
+/// A type reference in GraphQL syntax, e.g. `[ID!]!`
+fn type_string(type_ref: &JsonValue) -> String {
+    let inner = || type_ref.get("ofType").map(type_string).unwrap_or_default();
+    match type_ref.get("kind").and_then(JsonValue::as_str) {
+        Some("NON_NULL") => format!("{}!", inner()),
+        Some("LIST") => format!("[{}]", inner()),
+        _ => type_ref.get("name").and_then(JsonValue::as_str).unwrap_or("String").to_string(),
+    }
+}
+
+fn scalar_example(name: &str) -> JsonValue {
+    let lower = name.to_ascii_lowercase();
+    match name {
+        "Int" => JsonValue::from(1),
+        "Float" => JsonValue::from(1.5),
+        "Boolean" => JsonValue::Bool(true),
+        "ID" => JsonValue::from("1"),
+        _ if lower.contains("datetime") || lower.contains("timestamp") => JsonValue::from("2024-01-01T00:00:00Z"),
+        _ if lower.contains("date") => JsonValue::from("2024-01-01"),
+        _ if lower.contains("uuid") => JsonValue::from("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
+        _ if lower.contains("email") => JsonValue::from("user@example.com"),
+        _ if lower.contains("url") || lower.contains("uri") => JsonValue::from("https://example.com"),
+        _ if lower == "json" => JsonValue::Object(Map::new()),
+        _ => JsonValue::from("test"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::injection::{InjectionLocation, InjectionPoint};
+
+    fn type_ref(kind: &str, name: Option<&str>, of_type: Option<JsonValue>) -> JsonValue {
+        json!({ "kind": kind, "name": name, "ofType": of_type })
+    }
+
+    fn named(kind: &str, name: &str) -> JsonValue {
+        type_ref(kind, Some(name), None)
+    }
+
+    fn non_null(inner: JsonValue) -> JsonValue {
+        type_ref("NON_NULL", None, Some(inner))
+    }
+
+    #[test]
+    fn test_operations_from_schema() {
+        let response = json!({ "data": { "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": { "name": "Mutation" },
+            "types": [
+                { "kind": "OBJECT", "name": "Query", "fields": [
+                    { "name": "user", "args": [{ "name": "id", "type": non_null(named("SCALAR", "ID")) }],
+                      "type": named("OBJECT", "User") },
+                    { "name": "search", "args": [
+                        { "name": "filter", "type": named("INPUT_OBJECT", "Filter") },
+                        { "name": "order", "type": named("ENUM", "Order") }],
+                      "type": type_ref("LIST", None, Some(named("UNION", "Result"))) },
+                    { "name": "version", "args": [], "type": named("SCALAR", "String") }
+                ]},
+                { "kind": "OBJECT", "name": "User", "fields": [
+                    { "name": "id", "args": [], "type": non_null(named("SCALAR", "ID")) },
+                    { "name": "name", "args": [], "type": named("SCALAR", "String") },
+                    { "name": "posts", "args": [{ "name": "first", "type": non_null(named("SCALAR", "Int")) }],
+                      "type": named("OBJECT", "User") },
+                    { "name": "friend", "args": [], "type": named("OBJECT", "User") }
+                ]},
+                { "kind": "INPUT_OBJECT", "name": "Filter", "inputFields": [
+                    { "name": "term", "type": non_null(named("SCALAR", "String")) },
+                    { "name": "since", "type": named("SCALAR", "DateTime") }
+                ]},
+                { "kind": "ENUM", "name": "Order", "enumValues": [{ "name": "ASC" }, { "name": "DESC" }] },
+                { "kind": "UNION", "name": "Result" },
+                { "kind": "OBJECT", "name": "Mutation", "fields": [
+                    { "name": "deleteUser", "args": [{ "name": "id", "type": named("SCALAR", "ID") }],
+                      "type": named("SCALAR", "Boolean") }
+                ]}
+            ]
+        }}});
+        let schema = GraphQlSchema::from_introspection(&response).unwrap();
+        assert_eq!(schema.mutations(), vec!["deleteUser"]);
+
+        let mut endpoint = HttpRequest::get("https://api.example.com/graphql");
+        endpoint.set_header("Authorization", "Bearer t");
+        let operations = schema.operations(&endpoint, false);
+        assert_eq!(operations.len(), 3);
+        assert_eq!(schema.operations(&endpoint, true).len(), 4);
+
+        let body = |index: usize| -> JsonValue { serde_json::from_str(operations[index].request.body.as_deref().unwrap()).unwrap() };
+        assert_eq!(
+            body(0)["query"],
+            "query user($id: ID!) { user(id: $id) { id name friend { id name friend { id name } } } }"
+        );
+        assert_eq!(body(0)["variables"], json!({ "id": "1" }));
+        assert_eq!(body(1)["query"], "query search($filter: Filter, $order: Order) { search(filter: $filter, order: $order) { __typename } }");
+        assert_eq!(body(1)["variables"], json!({ "filter": { "term": "test", "since": "2024-01-01T00:00:00Z" }, "order": "ASC" }));
+        assert_eq!(body(2)["query"], "query version { version }");
+
+        let user = &operations[0];
+        assert_eq!(user.request.method, "POST");
+        assert_eq!(user.request.header("Authorization"), Some("Bearer t"));
+        assert_eq!(user.parameters, vec!["$.variables.id"]);
+        // Queries are read-only even though they are POSTed; mutations are not
+        assert_eq!(user.request.risk(), RiskLevel::ReadOnly);
+        assert_eq!(schema.operations(&endpoint, true)[3].request.risk(), RiskLevel::StateChanging);
+
+        // Only arguments are injection points, never the query itself
+        let points = InjectionPoint::discover(&operations[1].request);
+        let names: Vec<&str> = points.iter().filter(|p| p.location == InjectionLocation::JsonPath).map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["$.variables.filter.since", "$.variables.filter.term", "$.variables.order"]);
+    }
+}
@@ -2,5 +2,9 @@
 
 pub mod sql_injection;
 pub mod discovery;
+pub mod param_miner;
+pub mod crawler;
 pub mod analyzer;
 pub mod smart_payloads;
+pub mod signatures;
+pub mod graphql;
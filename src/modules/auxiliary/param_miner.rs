@@ -0,0 +1,369 @@
+// src/modules/auxiliary/param_miner.rs
+
+// Hidden parameter mining
+
+use async_trait::async_trait;
+use crate::core::http_request::HttpRequest;
+use crate::core::request_engine::RequestEngine;
+use crate::core::safety::RiskLevel;
+use crate::modules::auxiliary::analyzer::{Baseline, ResponseAnalyzer, ResponseSnapshot};
+use crate::modules::auxiliary::discovery::{DiscoveredParameter, ParameterSource};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+use std::time::Instant;
+use url::Url;
+
+const BUILTIN_WORDLIST: &str = include_str!("parameters.txt");
+
+/// Names of the built-in wordlist, parsed on first use
+static BUILTIN_WORDS: LazyLock<Vec<String>> = LazyLock::new(|| parse_wordlist(BUILTIN_WORDLIST));
+
+/// Candidate names sent together in one request
+const DEFAULT_BATCH_SIZE: usize = 40;
+
+/// Loads of the unmodified request that make up the mining baseline
+const BASELINE_SAMPLES: usize = 3;
+
+/// Responses less similar than this to the baseline count as changed
+const CHANGED_SIMILARITY: f64 = 0.98;
+
+/// Upper bound on the requests of one mining run, including the baseline
+const MAX_REQUESTS: usize = 150;
+
+/// Names loaded from the user's wordlist, tried after the built-in ones
+static USER_WORDLIST: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Finds parameters the page does not advertise by sending batches of
+/// candidate names and isolating the ones that change the response
+pub struct ParameterMiner {
+    words: Vec<String>,
+    batch_size: usize,
+}
+
+impl ParameterMiner {
+    /// Creates a miner with the built-in wordlist and the user's wordlist, if any
+    pub fn new() -> Self {
+        let mut words = BUILTIN_WORDS.clone();
+        for word in USER_WORDLIST.read().unwrap().iter() {
+            if !words.contains(word) {
+                words.push(word.clone());
+            }
+        }
+        Self::with_words(words)
+    }
+
+    /// Creates a miner that tries only the given names
+    pub fn with_words(words: Vec<String>) -> Self {
+        Self { words, batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Loads a wordlist (one name per line, `#` starts a comment) whose names
+    /// every miner tries for the rest of the process
+    pub fn init_user_wordlist(path: Option<&Path>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let words = match path {
+            Some(path) => {
+                let source = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read parameter wordlist {}: {}", path.display(), e))?;
+                let words = parse_wordlist(&source);
+                log::info!("Loaded {} parameter names from {}", words.len(), path.display());
+                words
+            }
+            None => Vec::new(),
+        };
+
+        *USER_WORDLIST.write().unwrap() = words;
+        Ok(())
+    }
+
+    /// Mines query parameters of `request`, skipping names in `known` and those
+    /// the request already has
+    pub async fn mine(
+        &self,
+        engine: &RequestEngine,
+        request: &HttpRequest,
+        known: &HashSet<String>,
+    ) -> Result<Vec<DiscoveredParameter>, Box<dyn std::error::Error + Send + Sync>> {
+        let url = Url::parse(&request.url)?;
+        let present: HashSet<String> = url.query_pairs().map(|(name, _)| name.into_owned()).collect();
+        let candidates: Vec<String> = self
+            .words
+            .iter()
+            .filter(|word| !known.contains(*word) && !present.contains(*word))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let baseline = ResponseAnalyzer::fetch_baseline(engine, request, BASELINE_SAMPLES, "parameter mining").await?;
+        let prefix = ResponseAnalyzer::canary_prefix();
+        let mut session = MiningSession {
+            responder: engine,
+            request,
+            baseline,
+            canaries: HashMap::new(),
+            prefix,
+            requests: BASELINE_SAMPLES,
+            reflection_reliable: true,
+        };
+
+        // Made-up names show whether the page reacts to any extra parameter
+        let control: Vec<String> = (0..self.batch_size).map(|i| format!("{}n{}", session.prefix, i)).collect();
+        let probe = session.probe(&control).await?;
+        if probe.changed {
+            log::info!("{} reacts to unknown parameters; skipping parameter mining", request.url);
+            return Ok(Vec::new());
+        }
+        // Pages that echo the whole URL reflect every value, which says nothing
+        session.reflection_reliable = probe.reflected.is_empty();
+
+        let mut found: Vec<String> = Vec::new();
+        for batch in candidates.chunks(self.batch_size) {
+            if session.requests >= MAX_REQUESTS {
+                log::warn!("Parameter mining stopped after {} requests", session.requests);
+                break;
+            }
+
+            let probe = session.probe(batch).await?;
+            if session.reflection_reliable {
+                found.extend(probe.reflected.iter().cloned());
+            }
+            if probe.changed {
+                let rest: Vec<String> = batch.iter().filter(|name| !found.contains(*name)).cloned().collect();
+                found.extend(session.isolate(rest).await?);
+            }
+        }
+
+        log::info!(
+            "Mined {} hidden parameters on {} with {} requests{}",
+            found.len(),
+            request.url,
+            session.requests,
+            if found.is_empty() { String::new() } else { format!(": {}", found.join(", ")) }
+        );
+        Ok(found
+            .into_iter()
+            .map(|name| DiscoveredParameter { name, value: "1".to_string(), source: ParameterSource::Mined })
+            .collect())
+    }
+}
+
+/// Default implementation for ParameterMiner
+impl Default for ParameterMiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends the requests of a mining run and captures the responses
+#[async_trait]
+trait Responder: Sync {
+    async fn respond(&self, request: &HttpRequest) -> Result<ResponseSnapshot, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl Responder for RequestEngine {
+    async fn respond(&self, request: &HttpRequest) -> Result<ResponseSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+        let started = Instant::now();
+        let response = self.send(request, "parameter mining", RiskLevel::ReadOnly).await?;
+        Ok(ResponseSnapshot::capture(response, started).await)
+    }
+}
+
+/// State shared by the requests of one mining run
+struct MiningSession<'a> {
+    responder: &'a dyn Responder,
+    request: &'a HttpRequest,
+    baseline: Baseline,
+    /// Unique value sent with each name, so reflections can be traced back
+    canaries: HashMap<String, String>,
+    prefix: String,
+    requests: usize,
+    reflection_reliable: bool,
+}
+
+/// What sending a group of names did to the response
+struct Probe {
+    changed: bool,
+    /// Names whose canary value appears in the response
+    reflected: Vec<String>,
+}
+
+impl MiningSession<'_> {
+    fn canary(&mut self, name: &str) -> String {
+        let next = self.canaries.len();
+        let prefix = &self.prefix;
+        // The trailing letter keeps one canary from being a prefix of another
+        self.canaries.entry(name.to_string()).or_insert_with(|| format!("{}{}z", prefix, next)).clone()
+    }
+
+    /// Sends the request with every name added as a query parameter
+    async fn probe(&mut self, names: &[String]) -> Result<Probe, Box<dyn std::error::Error + Send + Sync>> {
+        let pairs: Vec<(String, String)> = names.iter().map(|name| (name.clone(), self.canary(name))).collect();
+
+        let mut request = self.request.clone();
+        request.url = with_parameters(&request.url, &pairs)?;
+        let suffix = added_suffix(&self.request.url, &request.url);
+        self.requests += 1;
+
+        let mut snapshot = self.responder.respond(&request).await?;
+
+        let reflected = pairs
+            .iter()
+            .filter(|(_, canary)| snapshot.body.contains(canary.as_str()))
+            .map(|(name, _)| name.clone())
+            .collect();
+        // Pages that echo their own URL would otherwise change with every batch
+        if !suffix.is_empty() {
+            snapshot.body = snapshot.body.replace(&suffix, "").replace(&suffix.replace('&', "&amp;"), "");
+        }
+        for (name, canary) in &pairs {
+            snapshot.body = snapshot.body.replace(&format!("{}={}", name, canary), "").replace(canary.as_str(), "");
+        }
+
+        let comparison = ResponseAnalyzer::compare(&self.baseline, &snapshot, None);
+        let changed = comparison.status_changed
+            || !comparison.changed_headers.is_empty()
+            || comparison.similarity < CHANGED_SIMILARITY;
+        Ok(Probe { changed, reflected })
+    }
+
+    /// Splits a group that changed the response in halves until the names
+    /// responsible are found; each is confirmed by a request of its own, and
+    /// a group of one already was
+    async fn isolate(&mut self, group: Vec<String>) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        if group.len() == 1 {
+            return Ok(group);
+        }
+        let mut found = Vec::new();
+        let mut pending = vec![group];
+
+        while let Some(group) = pending.pop() {
+            if self.requests >= MAX_REQUESTS {
+                break;
+            }
+            let (left, right) = group.split_at(group.len() / 2);
+            for half in [left, right] {
+                if !self.probe(half).await?.changed {
+                    continue;
+                }
+                if half.len() == 1 {
+                    found.extend_from_slice(half);
+                } else {
+                    pending.push(half.to_vec());
+                }
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Appends the parameters to the query string of a URL
+fn with_parameters(url: &str, pairs: &[(String, String)]) -> Result<String, url::ParseError> {
+    let mut url = Url::parse(url)?;
+    url.query_pairs_mut().extend_pairs(pairs);
+    Ok(url.to_string())
+}
+
+/// The part `with_parameters` appended to a URL's query string, including the
+/// leading `?` or `&`
+fn added_suffix(original: &str, extended: &str) -> String {
+    let without_fragment = |url: &str| url.split('#').next().unwrap_or_default().to_string();
+    let (original, extended) = (without_fragment(original), without_fragment(extended));
+    extended.strip_prefix(original.as_str()).unwrap_or_default().to_string()
+}
+
+/// Names of a wordlist, without comments, blank lines or duplicates
+fn parse_wordlist(source: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for line in source.lines() {
+        let word = line.split('#').next().unwrap_or_default().trim();
+        if !word.is_empty() && !words.iter().any(|w| w == word) {
+            words.push(word.to_string());
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wordlists() {
+        let builtin = &*BUILTIN_WORDS;
+        assert!(builtin.len() > 100);
+        assert!(builtin.contains(&"debug".to_string()));
+        assert!(!builtin.iter().any(|w| w.starts_with('#') || w.contains(' ')));
+
+        let words = parse_wordlist("# comment\nadmin\n\n  trace  # inline\nadmin\n");
+        assert_eq!(words, vec!["admin", "trace"]);
+    }
+
+    /// A page that shows extra content when `debug` or `admin` is sent
+    struct FakeSite;
+
+    #[async_trait]
+    impl Responder for FakeSite {
+        async fn respond(&self, request: &HttpRequest) -> Result<ResponseSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+            let mut body = "<h1>Shop</h1> <p>Red shoes, size 42, in stock and ready to ship</p>".to_string();
+            for name in ["debug", "admin"] {
+                if request.url.contains(&format!("&{}=", name)) || request.url.contains(&format!("?{}=", name)) {
+                    body.push_str(&format!(" <pre>{} panel enabled with extra output</pre>", name));
+                }
+            }
+            Ok(ResponseSnapshot { status: 200, body, ..ResponseSnapshot::default() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_isolate_splits_batches() {
+        let request = HttpRequest::get("http://shop.example.com/?id=1");
+        let page = FakeSite.respond(&request).await.unwrap();
+        let mut session = MiningSession {
+            responder: &FakeSite,
+            request: &request,
+            baseline: Baseline::from_samples(&[page.clone(), page]),
+            canaries: HashMap::new(),
+            prefix: "rlxtest".to_string(),
+            requests: 0,
+            reflection_reliable: true,
+        };
+
+        let words: Vec<String> = (0..14).map(|i| format!("p{}", i)).chain(["debug".into(), "admin".into()]).collect();
+        assert!(session.probe(&words).await.unwrap().changed);
+        assert!(!session.probe(&words[..14]).await.unwrap().changed);
+
+        session.requests = 0;
+        let mut found = session.isolate(words).await.unwrap();
+        found.sort();
+        assert_eq!(found, vec!["admin", "debug"]);
+        // Halves of 8, 4 and 2 names, then the two names on their own, none probed twice
+        assert_eq!(session.requests, 8);
+        assert_eq!(session.isolate(vec!["debug".to_string()]).await.unwrap(), vec!["debug"]);
+        assert_eq!(session.requests, 8);
+    }
+
+    #[test]
+    fn test_with_parameters() {
+        let pairs = vec![("debug".to_string(), "rlx1z".to_string()), ("a b".to_string(), "rlx2z".to_string())];
+        assert_eq!(
+            with_parameters("http://example.com/p?id=1#top", &pairs).unwrap(),
+            "http://example.com/p?id=1&debug=rlx1z&a+b=rlx2z#top"
+        );
+        assert_eq!(
+            added_suffix("http://example.com/p?id=1#top", "http://example.com/p?id=1&debug=rlx1z#top"),
+            "&debug=rlx1z"
+        );
+    }
+}
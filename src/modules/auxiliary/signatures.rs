@@ -0,0 +1,221 @@
+// src/modules/auxiliary/signatures.rs
+
+// Error signature database
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::LazyLock;
+
+const BUILTIN_SIGNATURES: &str = include_str!("signatures.yaml");
+
+/// Longest matched text kept in a `SignatureMatch`
+const MAX_MATCH_LEN: usize = 200;
+
+static BUILTIN: LazyLock<SignatureDatabase> = LazyLock::new(|| {
+    SignatureDatabase::from_yaml(BUILTIN_SIGNATURES).expect("built-in error signatures are valid")
+});
+
+/// Kind of problem an error message reveals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorClass {
+    Sql,
+    TemplateEngine,
+    XmlParser,
+    StackTrace,
+    PathDisclosure,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Sql => "sql",
+            ErrorClass::TemplateEngine => "template-engine",
+            ErrorClass::XmlParser => "xml-parser",
+            ErrorClass::StackTrace => "stack-trace",
+            ErrorClass::PathDisclosure => "path-disclosure",
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A signature as written in a signature file
+#[derive(Debug, Deserialize)]
+struct SignatureDefinition {
+    id: String,
+    technology: String,
+    class: ErrorClass,
+    pattern: String,
+}
+
+/// A compiled error signature
+#[derive(Debug, Clone)]
+pub struct ErrorSignature {
+    pub id: String,
+    /// Product that emits the message, e.g. `MySQL` or `Jinja2`
+    pub technology: String,
+    pub class: ErrorClass,
+    pattern: Regex,
+}
+
+/// A signature found in a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureMatch {
+    pub signature_id: String,
+    pub technology: String,
+    pub class: ErrorClass,
+    /// The matched text, truncated to a reasonable length
+    pub matched: String,
+}
+
+impl fmt::Display for SignatureMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {}): \"{}\"", self.signature_id, self.technology, self.class, self.matched)
+    }
+}
+
+/// Regex signatures of error messages, tagged by technology and class
+#[derive(Debug, Clone, Default)]
+pub struct SignatureDatabase {
+    signatures: Vec<ErrorSignature>,
+}
+
+impl SignatureDatabase {
+    /// The signatures shipped with the scanner
+    pub fn builtin() -> &'static SignatureDatabase {
+        &BUILTIN
+    }
+
+    /// Parses a YAML list of signatures (`id`, `technology`, `class`, `pattern`)
+    pub fn from_yaml(source: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let definitions: Vec<SignatureDefinition> = serde_yaml::from_str(source)?;
+        let mut database = Self::default();
+        for definition in definitions {
+            database.add(&definition.id, &definition.technology, definition.class, &definition.pattern)?;
+        }
+        Ok(database)
+    }
+
+    /// Adds a signature; the pattern is matched case-insensitively
+    pub fn add(
+        &mut self,
+        id: &str,
+        technology: &str,
+        class: ErrorClass,
+        pattern: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let pattern = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("invalid pattern for signature '{}': {}", id, e))?;
+
+        self.signatures.push(ErrorSignature {
+            id: id.to_string(),
+            technology: technology.to_string(),
+            class,
+            pattern,
+        });
+        Ok(())
+    }
+
+    pub fn signatures(&self) -> &[ErrorSignature] {
+        &self.signatures
+    }
+
+    /// Finds every signature in the text, at most one match per signature
+    pub fn scan(&self, text: &str) -> Vec<SignatureMatch> {
+        self.scan_excluding(text, None)
+    }
+
+    /// Like `scan`, but ignores matches whose text already appears in `baseline`,
+    /// ignoring case, so messages that are part of the normal page do not count
+    pub fn scan_new(&self, baseline: &str, text: &str) -> Vec<SignatureMatch> {
+        self.scan_excluding(text, Some(baseline))
+    }
+
+    fn scan_excluding(&self, text: &str, baseline: Option<&str>) -> Vec<SignatureMatch> {
+        let baseline = baseline.map(str::to_lowercase);
+        self.signatures
+            .iter()
+            .filter_map(|signature| {
+                signature
+                    .pattern
+                    .find_iter(text)
+                    .map(|m| m.as_str())
+                    .find(|matched| baseline.as_ref().is_none_or(|b| !b.contains(&matched.to_lowercase())))
+                    .map(|matched| SignatureMatch {
+                        signature_id: signature.id.clone(),
+                        technology: signature.technology.clone(),
+                        class: signature.class,
+                        matched: truncate(matched),
+                    })
+            })
+            .collect()
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_MATCH_LEN) {
+        Some((index, _)) => format!("{}...", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(matches: &[SignatureMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.signature_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_builtin_signatures() {
+        let db = SignatureDatabase::builtin();
+        for class in [
+            ErrorClass::Sql,
+            ErrorClass::TemplateEngine,
+            ErrorClass::XmlParser,
+            ErrorClass::StackTrace,
+            ErrorClass::PathDisclosure,
+        ] {
+            assert!(db.signatures().iter().any(|s| s.class == class), "no {} signatures", class);
+        }
+
+        let mysql = db.scan("You have an error in your SQL syntax; check the manual near ''1''' at line 1");
+        assert_eq!(ids(&mysql), vec!["mysql-syntax"]);
+        assert_eq!(mysql[0].technology, "MySQL");
+
+        let php = db.scan("<b>Warning</b>: mysqli_query() expects parameter 1 in <b>/var/www/html/item.php</b> on line <b>12</b>");
+        assert!(ids(&php).contains(&"mysql-php-warning"));
+        assert!(ids(&php).contains(&"php-error"));
+        assert!(ids(&php).contains(&"unix-path"));
+
+        assert_eq!(ids(&db.scan("ORA-01756: quoted string not properly terminated")), vec!["oracle-ora", "oracle-driver"]);
+        assert_eq!(ids(&db.scan("jinja2.exceptions.TemplateSyntaxError: unexpected '}'")), vec!["jinja2"]);
+
+        // Generic wording in scripts is not an error page
+        assert!(db.scan("if (e instanceof SyntaxError) { report('syntax error'); }").is_empty());
+    }
+
+    #[test]
+    fn test_scan_new_ignores_baseline_text() {
+        let db = SignatureDatabase::builtin();
+        let baseline = "<pre>Docs: a PDOException is thrown when ...</pre>";
+        let response = "<pre>Docs: a PDOException is thrown when ...</pre> SQLSTATE[42000]: Syntax error";
+
+        assert!(db.scan_new(baseline, baseline).is_empty());
+        let matches = db.scan_new(baseline, response);
+        assert_eq!(ids(&matches), vec!["pdo"]);
+        assert_eq!(matches[0].matched, "SQLSTATE[42000]");
+
+        // The page already shows the message, only in a different case
+        assert!(db.scan_new(&format!("{} sqlstate[42000]", baseline.to_uppercase()), response).is_empty());
+    }
+}
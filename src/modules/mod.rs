@@ -2,3 +2,12 @@
 
 pub mod auxiliary;
 
+use crate::core::ModuleHandler;
+use auxiliary::graphql::GraphQlScanner;
+use auxiliary::sql_injection::SQLInjectionScanner;
+
+/// Registers every built-in security module with the given handler
+pub fn load_all_modules(handler: &mut ModuleHandler) {
+    handler.register_module(SQLInjectionScanner::new());
+    handler.register_module(GraphQlScanner::new());
+}
@@ -0,0 +1,249 @@
+// src/payloads/mod.rs
+
+use crate::core::RiskLevel;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+/// Built-in payload libraries as `(file name, source)`
+const BUILTIN_LIBRARIES: &[(&str, &str)] = &[(
+    "sql_injection.yaml",
+    include_str!("library/sql_injection.yaml"),
+)];
+
+/// Built-in libraries, parsed on first use
+static BUILTIN: LazyLock<Result<Vec<PayloadLibrary>, PayloadLibraryError>> = LazyLock::new(|| {
+    BUILTIN_LIBRARIES
+        .iter()
+        .map(|(name, source)| PayloadLibrary::parse(Path::new(name), source))
+        .collect()
+});
+
+/// Libraries loaded from user directories, merged into every module's payloads
+static USER_LIBRARIES: RwLock<Vec<PayloadLibrary>> = RwLock::new(Vec::new());
+
+/// How a payload tries to exploit the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Technique {
+    Error,
+    Boolean,
+    Time,
+    Union,
+}
+
+impl Technique {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Technique::Error => "error",
+            Technique::Boolean => "boolean",
+            Technique::Time => "time",
+            Technique::Union => "union",
+        }
+    }
+
+    /// How the test sending payloads of this technique recognizes a success
+    pub fn detection(&self) -> Detection {
+        match self {
+            Technique::Error => Detection::ErrorMessage,
+            Technique::Boolean => Detection::ResponseDiff,
+            Technique::Time => Detection::Delay,
+            Technique::Union => Detection::Reflection,
+        }
+    }
+}
+
+/// How a successful payload is expected to show up in the response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Detection {
+    /// A database or framework error message appears
+    ErrorMessage,
+    /// The response differs from the baseline or from a control payload
+    ResponseDiff,
+    /// The response is delayed by the requested time
+    Delay,
+    /// Injected content is reflected in the response
+    Reflection,
+}
+
+impl Detection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Detection::ErrorMessage => "error-message",
+            Detection::ResponseDiff => "response-diff",
+            Detection::Delay => "delay",
+            Detection::Reflection => "reflection",
+        }
+    }
+}
+
+/// A single payload entry of a library file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadEntry {
+    /// Text appended to the original value of the injection point
+    pub text: String,
+    /// Counterpart sent for comparison, e.g. the always-false condition of a boolean pair
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control: Option<String>,
+    /// Database engine the payload is written for; `None` means any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dbms: Option<String>,
+    pub technique: Technique,
+    #[serde(default = "default_risk")]
+    pub risk: RiskLevel,
+    pub detection: Detection,
+}
+
+fn default_risk() -> RiskLevel {
+    RiskLevel::ReadOnly
+}
+
+/// A set of payloads for one module, as stored in a YAML or JSON file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadLibrary {
+    /// Name of the module the payloads are meant for, e.g. `sql_injection`
+    pub module: String,
+    #[serde(default)]
+    pub payloads: Vec<PayloadEntry>,
+}
+
+/// Error returned when a payload library file cannot be loaded
+#[derive(Debug, Clone)]
+pub struct PayloadLibraryError {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl fmt::Display for PayloadLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid payload library {}: {}", self.path.display(), self.reason)
+    }
+}
+
+impl std::error::Error for PayloadLibraryError {}
+
+impl PayloadLibrary {
+    /// Parses a library from YAML or, for `.json` files, JSON source
+    pub fn parse(path: &Path, source: &str) -> Result<Self, PayloadLibraryError> {
+        let error = |reason: String| PayloadLibraryError { path: path.to_path_buf(), reason };
+
+        let library: Self = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(source).map_err(|e| error(e.to_string()))?
+        } else {
+            serde_yaml::from_str(source).map_err(|e| error(e.to_string()))?
+        };
+
+        if let Some(index) = library.payloads.iter().position(|p| p.technique == Technique::Boolean && p.control.is_none()) {
+            return Err(error(format!("boolean payload #{} has no `control`", index + 1)));
+        }
+        if let Some((index, entry)) =
+            library.payloads.iter().enumerate().find(|(_, p)| p.detection != p.technique.detection())
+        {
+            return Err(error(format!(
+                "payload #{} has detection `{}`, but {} payloads are detected by `{}`",
+                index + 1,
+                entry.detection.as_str(),
+                entry.technique.as_str(),
+                entry.technique.detection().as_str()
+            )));
+        }
+        Ok(library)
+    }
+
+    /// Loads a single library file
+    pub fn load(path: &Path) -> Result<Self, PayloadLibraryError> {
+        let source = std::fs::read_to_string(path).map_err(|e| PayloadLibraryError {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        Self::parse(path, &source)
+    }
+
+    /// Loads every `.yaml`, `.yml` and `.json` file in a directory, in file name order
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>, PayloadLibraryError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| PayloadLibraryError {
+            path: dir.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "yaml" | "yml" | "json"))
+            })
+            .collect();
+        paths.sort();
+
+        paths.iter().map(|path| Self::load(path)).collect()
+    }
+
+    /// Loads the libraries in the given directories and makes them available to
+    /// every module for the rest of the process
+    pub fn init_user_dirs(dirs: &[PathBuf]) -> Result<(), PayloadLibraryError> {
+        let mut libraries = Vec::new();
+        for dir in dirs {
+            let loaded = Self::load_dir(dir)?;
+            log::info!("Loaded {} payload libraries from {}", loaded.len(), dir.display());
+            libraries.extend(loaded);
+        }
+
+        *USER_LIBRARIES.write().unwrap() = libraries;
+        Ok(())
+    }
+
+    /// Built-in payloads for a module followed by those from user directories,
+    /// without duplicates
+    pub fn for_module(module: &str) -> Result<Vec<PayloadEntry>, PayloadLibraryError> {
+        let builtin = BUILTIN.clone()?;
+        let user = USER_LIBRARIES.read().unwrap().clone();
+
+        let mut payloads: Vec<PayloadEntry> = Vec::new();
+        for library in builtin.into_iter().chain(user).filter(|library| library.module == module) {
+            for entry in library.payloads {
+                let duplicate = payloads
+                    .iter()
+                    .any(|p| p.technique == entry.technique && p.text == entry.text && p.control == entry.control);
+                if !duplicate {
+                    payloads.push(entry);
+                }
+            }
+        }
+        Ok(payloads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_libraries_parse() {
+        let payloads = PayloadLibrary::for_module("sql_injection").unwrap();
+        assert!(payloads.iter().any(|p| p.technique == Technique::Error));
+        assert!(payloads.iter().any(|p| p.technique == Technique::Time && p.risk == RiskLevel::Heavy));
+        assert!(payloads.iter().filter(|p| p.technique == Technique::Boolean).all(|p| p.control.is_some()));
+        assert!(PayloadLibrary::for_module("unknown").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_json_and_yaml() {
+        let yaml = "module: sql_injection\npayloads:\n  - text: \"' --\"\n    dbms: mysql\n    technique: error\n    detection: error-message\n";
+        let library = PayloadLibrary::parse(Path::new("extra.yml"), yaml).unwrap();
+        assert_eq!(library.payloads[0].dbms.as_deref(), Some("mysql"));
+        assert_eq!(library.payloads[0].risk, RiskLevel::ReadOnly);
+
+        let json = r#"{"module":"sql_injection","payloads":[{"text":" AND 1=1","technique":"boolean","detection":"response-diff"}]}"#;
+        let error = PayloadLibrary::parse(Path::new("extra.json"), json).unwrap_err();
+        assert!(error.reason.contains("control"));
+
+        let mismatched = "module: sql_injection\npayloads:\n  - text: \"'\"\n    technique: error\n    detection: delay\n";
+        let error = PayloadLibrary::parse(Path::new("extra.yml"), mismatched).unwrap_err();
+        assert!(error.reason.contains("error payloads are detected by `error-message`"));
+    }
+}
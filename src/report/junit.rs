@@ -0,0 +1,162 @@
+// src/report/junit.rs
+
+use crate::core::{ScanRecord, SeverityLevel};
+
+/// Renders stored scans as a JUnit XML document for CI systems.
+///
+/// Each scan becomes a `<testsuite>` named after its module and each finding
+/// a `<testcase>`. Findings at or above `fail_on` (all findings when `None`)
+/// are reported as failures; lower ones are marked skipped so they stay
+/// visible without failing the build. A scan without findings yields a single
+/// passing test case for the target.
+pub fn render_junit(records: &[ScanRecord], fail_on: Option<SeverityLevel>) -> String {
+    let is_failure = |severity: SeverityLevel| fail_on.is_none_or(|threshold| severity >= threshold);
+
+    let total_tests: usize = records.iter().map(|r| r.result.vulnerabilities.len().max(1)).sum();
+    let total_failures: usize = records
+        .iter()
+        .map(|r| r.result.vulnerabilities.iter().filter(|v| is_failure(v.severity)).count())
+        .sum();
+    let total_time: f64 = records.iter().map(|r| r.result.duration.as_secs_f64()).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"redlines-x\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        total_tests, total_failures, total_time
+    ));
+
+    for record in records {
+        let vulns = &record.result.vulnerabilities;
+        let failures = vulns.iter().filter(|v| is_failure(v.severity)).count();
+        let skipped = vulns.len() - failures;
+
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\" timestamp=\"{}\">\n",
+            escape(&record.module),
+            vulns.len().max(1),
+            failures,
+            skipped,
+            record.result.duration.as_secs_f64(),
+            record.started_at.format("%Y-%m-%dT%H:%M:%S"),
+        ));
+        xml.push_str("    <properties>\n");
+        xml.push_str(&format!("      <property name=\"scan_id\" value=\"{}\"/>\n", escape(&record.id)));
+        xml.push_str(&format!("      <property name=\"target\" value=\"{}\"/>\n", escape(&record.target)));
+        xml.push_str("    </properties>\n");
+
+        if vulns.is_empty() {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\"/>\n",
+                escape(&record.module),
+                escape(&record.target)
+            ));
+        }
+
+        for vuln in vulns {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n",
+                escape(&record.module),
+                escape(&format!("[{}] {}", vuln.severity, vuln.title))
+            ));
+
+            if is_failure(vuln.severity) {
+                let mut details = format!("{}\n\n{}", vuln.description, vuln.evidence);
+                if !vuln.remediation.is_empty() {
+                    details.push_str(&format!("\n\nRemediation: {}", vuln.remediation));
+                }
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                    escape(&vuln.title),
+                    vuln.severity,
+                    escape(&details)
+                ));
+            } else {
+                xml.push_str(&format!(
+                    "      <skipped message=\"{} severity is below the failure threshold\"/>\n",
+                    vuln.severity
+                ));
+            }
+
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Escapes text for use in XML attributes and character data
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ScanResult, Vulnerability};
+    use chrono::Utc;
+    use std::time::Duration;
+
+    fn record(severities: &[SeverityLevel]) -> ScanRecord {
+        let vulnerabilities = severities
+            .iter()
+            .map(|severity| Vulnerability {
+                title: "SQL Injection in parameter 'id'".to_string(),
+                description: "Error-based <injection>".to_string(),
+                severity: *severity,
+                evidence: "Payload: ' OR '1'='1".to_string(),
+                request: None,
+                response: None,
+                remediation: String::new(),
+                dbms: None,
+                reflection: None,
+            })
+            .collect();
+
+        ScanRecord::new(
+            "http://example.com/?a=1&b=2",
+            "sql_injection",
+            Utc::now(),
+            ScanResult {
+                success: true,
+                vulnerabilities,
+                warnings: Vec::new(),
+                duration: Duration::from_millis(1500),
+            },
+        )
+    }
+
+    #[test]
+    fn test_findings_below_threshold_are_skipped() {
+        let xml = render_junit(&[record(&[SeverityLevel::Low, SeverityLevel::High])], Some(SeverityLevel::High));
+
+        assert!(xml.contains("tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"1\""));
+        assert!(xml.contains("<failure message=\"SQL Injection in parameter &apos;id&apos;\" type=\"High\">"));
+        assert!(xml.contains("Error-based &lt;injection&gt;"));
+        assert!(xml.contains("value=\"http://example.com/?a=1&amp;b=2\""));
+    }
+
+    #[test]
+    fn test_clean_scan_yields_passing_testcase() {
+        let xml = render_junit(&[record(&[])], None);
+
+        assert!(xml.contains("<testsuites name=\"redlines-x\" tests=\"1\" failures=\"0\""));
+        assert!(xml.contains("<testcase classname=\"sql_injection\" name=\"http://example.com/?a=1&amp;b=2\"/>"));
+        assert!(!xml.contains("<failure"));
+    }
+}
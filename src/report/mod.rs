@@ -0,0 +1,222 @@
+// src/report/mod.rs
+
+pub mod junit;
+
+pub use junit::render_junit;
+
+use crate::core::ScanRecord;
+use chrono::Utc;
+use minijinja::{context, Environment};
+use serde::Serialize;
+use std::path::Path;
+
+const HTML_TEMPLATE: &str = include_str!("templates/report.html");
+const MARKDOWN_TEMPLATE: &str = include_str!("templates/report.md");
+
+/// Output formats supported by the report generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Name of the template used to render this format
+    fn template_name(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "report.html",
+            ReportFormat::Markdown => "report.md",
+        }
+    }
+
+    /// File extension for reports in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Renders stored scans into self-contained HTML and Markdown reports
+pub struct ReportGenerator {
+    env: Environment<'static>,
+}
+
+impl ReportGenerator {
+    /// Creates a generator using the built-in templates
+    pub fn new() -> Self {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.add_filter("fence", fence);
+        env.add_template("report.html", HTML_TEMPLATE)
+            .expect("built-in HTML template is valid");
+        env.add_template("report.md", MARKDOWN_TEMPLATE)
+            .expect("built-in Markdown template is valid");
+
+        Self { env }
+    }
+
+    /// Creates a generator where `report.html` and/or `report.md` found in
+    /// `dir` replace the built-in templates
+    pub fn with_template_dir(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut generator = Self::new();
+
+        for format in [ReportFormat::Html, ReportFormat::Markdown] {
+            let name = format.template_name();
+            let path = dir.join(name);
+            if path.is_file() {
+                let source = std::fs::read_to_string(&path)?;
+                generator.env.add_template_owned(name, source)?;
+                log::info!("Using custom report template: {}", path.display());
+            }
+        }
+
+        Ok(generator)
+    }
+
+    /// Renders the given scan in the requested format
+    pub fn render(&self, record: &ScanRecord, format: ReportFormat) -> Result<String, minijinja::Error> {
+        let template = self.env.get_template(format.template_name())?;
+
+        let summary: Vec<SeveritySummary> = record
+            .result
+            .severity_counts()
+            .into_iter()
+            .map(|(severity, count)| SeveritySummary { severity: severity.to_string(), count })
+            .collect();
+
+        let mut findings: Vec<_> = record.result.vulnerabilities.iter().collect();
+        findings.sort_by_key(|v| std::cmp::Reverse(v.severity));
+        let findings: Vec<Finding> = findings
+            .into_iter()
+            .enumerate()
+            .map(|(index, vuln)| Finding {
+                number: index + 1,
+                title: &vuln.title,
+                severity: vuln.severity.to_string(),
+                description: &vuln.description,
+                evidence: &vuln.evidence,
+                request: vuln.request.as_deref(),
+                response: vuln.response.as_deref(),
+                remediation: &vuln.remediation,
+                dbms: vuln.dbms.as_deref(),
+                reflection: vuln.reflection.as_deref(),
+            })
+            .collect();
+
+        template.render(context! {
+            scan => context! {
+                id => &record.id,
+                target => &record.target,
+                module => &record.module,
+                started_at => record.started_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                duration => format!("{:.2?}", record.result.duration),
+            },
+            summary => summary,
+            total => findings.len(),
+            findings => findings,
+            warnings => &record.result.warnings,
+            generated_at => Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        })
+    }
+}
+
+/// Wraps text in a Markdown code block whose backtick fence is longer than any
+/// run of backticks inside it, so the content cannot close the block early
+fn fence(content: &str, info: Option<&str>) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat((longest + 1).max(3));
+    format!("{}{}\n{}\n{}", fence, info.unwrap_or_default(), content, fence)
+}
+
+/// Default implementation for ReportGenerator
+impl Default for ReportGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of findings at one severity level, as exposed to templates
+#[derive(Serialize)]
+struct SeveritySummary {
+    severity: String,
+    count: usize,
+}
+
+/// A single finding as exposed to templates
+#[derive(Serialize)]
+struct Finding<'a> {
+    number: usize,
+    title: &'a str,
+    severity: String,
+    description: &'a str,
+    evidence: &'a str,
+    request: Option<&'a str>,
+    response: Option<&'a str>,
+    remediation: &'a str,
+    dbms: Option<&'a str>,
+    reflection: Option<&'a str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ScanResult, SeverityLevel, Vulnerability};
+    use std::time::Duration;
+
+    fn sample_record() -> ScanRecord {
+        let vuln = |title: &str, severity| Vulnerability {
+            title: title.to_string(),
+            description: "Injected <script> payload".to_string(),
+            severity,
+            evidence: "Payload: ' OR '1'='1".to_string(),
+            request: Some("GET http://example.com/?id=1".to_string()),
+            response: Some("You have an error in your SQL syntax".to_string()),
+            remediation: "Use parameterized queries".to_string(),
+            dbms: Some("MySQL".to_string()),
+            reflection: None,
+        };
+
+        ScanRecord::new(
+            "http://example.com",
+            "sql_injection",
+            Utc::now(),
+            ScanResult {
+                success: true,
+                vulnerabilities: vec![
+                    vuln("Low finding", SeverityLevel::Low),
+                    vuln("Critical finding", SeverityLevel::Critical),
+                ],
+                warnings: Vec::new(),
+                duration: Duration::from_secs(3),
+            },
+        )
+    }
+
+    #[test]
+    fn test_html_report_escapes_and_orders_findings() {
+        let html = ReportGenerator::new().render(&sample_record(), ReportFormat::Html).unwrap();
+
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.find("Critical finding").unwrap() < html.find("Low finding").unwrap());
+    }
+
+    #[test]
+    fn test_markdown_report_contains_summary_and_evidence() {
+        let md = ReportGenerator::new().render(&sample_record(), ReportFormat::Markdown).unwrap();
+
+        assert!(md.contains("| Critical | 1 |"));
+        assert!(md.contains("| High | 0 |"));
+        assert!(md.contains("GET http://example.com/?id=1"));
+        assert!(md.contains("Use parameterized queries"));
+        assert!(md.contains("**Database:** MySQL"));
+    }
+
+    #[test]
+    fn test_fence_outgrows_backticks_in_content() {
+        assert_eq!(fence("id=1", Some("http")), "```http\nid=1\n```");
+        assert_eq!(fence("a ```` b", None), "`````\na ```` b\n`````");
+    }
+}